@@ -0,0 +1,154 @@
+//! 문제의 코드 블록을 로컬 rustc로 컴파일하고 실행해 [`CodeCheck`]에 적은 대로 동작하는지 확인함.
+//! rustdoc의 doctest처럼 코드 블록 하나를 파일 하나로 컴파일함.
+//! 사용할 컴파일러는 `RUSTC` 환경 변수로 바꿀 수 있음.
+
+use crate::{CodeCheck, ParsedChoice, ParsedQuestion};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 코드 블록을 컴파일할 때 사용하는 에디션
+const EDITION: &str = "2024";
+/// 실행한 코드가 이 시간 안에 끝나지 않으면 종료함
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+static NEXT_WORKSPACE: AtomicUsize = AtomicUsize::new(0);
+
+/// 코드 블록이 check에 적은 대로 동작하는지 확인함. 검사할 것이 없으면 컴파일하지 않음.
+pub fn check_code(code: &str, check: &CodeCheck) -> Result<(), String> {
+    match check {
+        CodeCheck::None => Ok(()),
+        CodeCheck::Compile => match compile(&Workspace::new()?, code)? {
+            Ok(_) => Ok(()),
+            Err(stderr) => Err(format!("code block is expected to compile, but rustc failed:\n{}", stderr)),
+        },
+        CodeCheck::CompileFail { error } => match (compile(&Workspace::new()?, code)?, error) {
+            (Ok(_), _) => Err("code block is marked compile_fail, but it compiles".to_string()),
+            (Err(stderr), Some(error)) if !stderr.contains(&format!("error[{}]", error)) => Err(format!(
+                "code block is expected to fail with {}, but rustc reported:\n{}",
+                error, stderr
+            )),
+            (Err(_), _) => Ok(()),
+        },
+        CodeCheck::Output(expected) => {
+            let output = run_code(code)?;
+            match output.trim_end() == expected.trim_end() {
+                true => Ok(()),
+                false => Err(format!(
+                    "code block is expected to print {:?}, but it printed {:?}",
+                    expected.trim_end(),
+                    output.trim_end()
+                )),
+            }
+        }
+    }
+}
+
+/// 실행 결과를 정답으로 사용하는 문제이면 코드 블록을 실행해 정답으로 쓸 표준 출력을 반환함.
+/// 그런 선택지가 없으면 코드를 실행하지 않고 None을 반환함.
+pub fn expected_output(question: &ParsedQuestion) -> Result<Option<String>, String> {
+    if !question.choices.iter().any(|v| matches!(v, ParsedChoice::Output { .. })) {
+        return Ok(None);
+    }
+    let output = run_code(&question.code)?;
+    Ok(Some(output.trim_end().to_string()))
+}
+
+/// 코드 블록을 컴파일하고 실행해 표준 출력을 반환함
+pub fn run_code(code: &str) -> Result<String, String> {
+    let workspace = Workspace::new()?;
+    let binary = match compile(&workspace, code)? {
+        Ok(v) => v,
+        Err(stderr) => return Err(format!("code block does not compile:\n{}", stderr)),
+    };
+
+    let mut child = Command::new(&binary)
+        .current_dir(&workspace.path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Unable to run compiled code: {}", e))?;
+
+    // 출력이 많아도 파이프가 막히지 않도록 따로 읽음
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() > RUN_TIMEOUT => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("code block did not finish within {} seconds", RUN_TIMEOUT.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Unable to wait for compiled code: {}", e)),
+        }
+    };
+
+    let output = reader
+        .join()
+        .unwrap()
+        .map_err(|e| format!("Unable to read output of compiled code: {}", e))?;
+    if !status.success() {
+        return Err(format!("code block exited with {}", status));
+    }
+    String::from_utf8(output).map_err(|_| "code block printed invalid UTF-8".to_string())
+}
+
+/// 코드를 컴파일함. rustc를 실행할 수 없으면 Err, 컴파일에 실패하면 Ok(Err(오류 메세지))를 반환함.
+fn compile(workspace: &Workspace, code: &str) -> Result<Result<PathBuf, String>, String> {
+    let source = workspace.path.join("main.rs");
+    let binary = workspace.path.join(format!("main{}", std::env::consts::EXE_SUFFIX));
+    std::fs::write(&source, code).map_err(|e| format!("Unable to write {}: {}", source.display(), e))?;
+
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(&rustc)
+        .arg("--edition")
+        .arg(EDITION)
+        .arg("--crate-name")
+        .arg("main")
+        .arg("-o")
+        .arg(&binary)
+        .arg(&source)
+        .current_dir(&workspace.path)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Unable to run {}: {}", Path::new(&rustc).display(), e))?;
+
+    match output.status.success() {
+        true => Ok(Ok(binary)),
+        false => Ok(Err(String::from_utf8_lossy(&output.stderr).trim_end().to_string())),
+    }
+}
+
+/// 코드 블록 하나를 컴파일하는 임시 폴더. drop 될 때 지움.
+struct Workspace {
+    path: PathBuf,
+}
+
+impl Workspace {
+    fn new() -> Result<Self, String> {
+        let path = std::env::temp_dir().join(format!(
+            "question-format-{}-{}",
+            std::process::id(),
+            NEXT_WORKSPACE.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path).map_err(|e| format!("Unable to create {}: {}", path.display(), e))?;
+        Ok(Workspace { path })
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,41 @@
+//! 코드 블록 색칠. 브라우저에서 highlight.js를 내려받지 않도록 빌드할 때 코드를 `<span class="hl-...">`로 감싼 HTML로 바꿈.
+//! 색은 클래스 이름만 정하고, 실제 색은 서버에 포함된 `highlight.css`가 정함.
+
+use std::sync::OnceLock;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// 페이지의 다른 클래스와 겹치지 않도록 모든 클래스 이름 앞에 `hl-`를 붙임
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+/// `highlight.css`를 만들 때 사용하는 테마
+const THEME: &str = "base16-ocean.dark";
+
+/// 러스트 코드를 색칠한 HTML로 바꿈. 결과는 `<pre><code>` 안에 그대로 넣을 수 있도록 escape 되어 있음.
+pub fn highlight_rust(code: &str) -> String {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let syntax = syntaxes
+        .find_syntax_by_extension("rs")
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        // 문법 정의를 해석하지 못하는 경우는 사실상 없지만, 그래도 실패하면 색 없이 보여줌
+        if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+            return escape_html(code);
+        }
+    }
+    generator.finalize()
+}
+
+/// highlight_rust가 만드는 클래스들의 색을 정한 CSS
+pub fn theme_css() -> String {
+    let themes = ThemeSet::load_defaults();
+    css_for_theme_with_class_style(&themes.themes[THEME], CLASS_STYLE).expect("default theme must be valid")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,693 @@
+//! 문제 마크다운 파일의 형식을 분석하는 라이브러리.
+//! include_questions! 매크로와 다른 도구들이 같은 규칙으로 문제를 읽을 수 있도록 분리함.
+
+pub mod compile;
+pub mod highlight;
+pub mod markdown;
+pub mod prepare;
+
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedQuestion {
+    /// front matter에 적힌 id. 없으면 None임.
+    pub id: Option<String>,
+    pub title: String,
+    pub description: String,
+    pub code: String,
+    /// 코드 블록 여는 줄에 적은 검사 방법
+    pub code_check: CodeCheck,
+    pub choices: Vec<ParsedChoice>,
+    /// 정답 선택지 번호들
+    pub answers: Vec<usize>,
+    /// `## 해설` 아래의 내용. 없으면 빈 문자열임.
+    pub explanation: String,
+    /// front matter의 `match:`에 적은 서술형 답 비교 방법
+    pub matching: MatchRule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedChoice {
+    /// 객관식
+    Choice { label: String },
+
+    /// 주관식. `label: [답1|답2]`처럼 인정하는 답을 여러 개 적을 수 있음.
+    Subjective { label: String, values: Vec<String> },
+
+    /// 숫자 답. `label: {4 ± 0.5 바이트}`처럼 허용 오차와 단위를 함께 적을 수 있음.
+    Numeric {
+        label: String,
+        value: f64,
+        /// 정답과의 차이가 이 값 이하이면 정답. 적지 않으면 0임.
+        tolerance: f64,
+        /// 입력 칸 옆에 표시하는 단위. 없으면 빈 문자열임.
+        unit: String,
+    },
+
+    /// 순서 문제의 항목. `1. 항목`처럼 번호 목록으로 적은 순서가 정답 순서임.
+    Step { label: String },
+
+    /// 코드 블록의 실행 결과를 적는 답. `label: [@output]`으로 적으며,
+    /// 정답은 빌드할 때 코드 블록을 실제로 실행한 표준 출력임.
+    Output { label: String },
+}
+
+/// 실행 결과를 정답으로 사용하는 선택지에 적는 값
+const OUTPUT_PLACEHOLDER: &str = "@output";
+
+/// 코드 블록이 실제 컴파일러에서 어떻게 동작해야 하는지. ```` ```rs compile_fail ````처럼 여는 줄에 적음.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CodeCheck {
+    /// 검사하지 않음
+    #[default]
+    None,
+    /// 컴파일에 성공해야 함
+    Compile,
+    /// 컴파일에 실패해야 함. `compile_fail=E0004`처럼 오류 코드를 적으면 그 오류로 실패해야 함.
+    CompileFail { error: Option<String> },
+    /// 컴파일하고 실행했을 때 표준 출력이 이 값과 같아야 함. 끝의 공백과 줄바꿈은 무시함.
+    Output(String),
+}
+
+/// 서술형 답을 정답과 비교하는 방법
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MatchRule {
+    /// 앞뒤 공백을 무시하고 영문 대소문자를 구분하지 않음
+    #[default]
+    Exact,
+    /// 모든 공백을 무시하고 영문 대소문자를 구분하지 않음
+    Whitespace,
+    /// 숫자로 읽어 차이가 tolerance 이하이면 정답
+    Numeric { tolerance: f64 },
+    /// 정답을 정규식으로 취급해 답 전체가 일치하면 정답
+    Regex,
+}
+
+/// 디렉토리에서 읽은 문제 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuestionFile {
+    /// front matter의 id, 없으면 확장자를 뺀 파일 이름
+    pub id: String,
+    pub file_name: String,
+    pub question: ParsedQuestion,
+}
+
+/// 마크다운 파일의 형식 오류. 어느 줄인지 알 수 없으면 line은 None임.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// 파일 하나에서 발견한 모든 형식 오류
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// 디렉토리를 읽다가 발견한 오류. file은 디렉토리 기준 상대 경로임.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    pub file: Option<String>,
+    pub diagnostic: Diagnostic,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum ParseMode {
+    FrontMatter,
+    Title,
+    Description,
+    Code,
+    Choices,
+    Explanation,
+}
+
+/// 마크다운 파일 하나를 분석함. 형식 오류는 가능한 한 모두 모아서 반환함.
+pub fn parse_question(contents: &str) -> Result<ParsedQuestion, ParseError> {
+    let mut diagnostics = vec![];
+    let mut id = None;
+    let mut title = None;
+    let mut parsing_mode = ParseMode::Title;
+    let mut description = String::new();
+    let mut code = String::new();
+    let mut code_start = 0;
+    let mut code_check = CodeCheck::None;
+    let mut choices = vec![];
+    let mut answers = vec![];
+    let mut explanation = String::new();
+    let mut matching = MatchRule::default();
+    // 설명에 쓴 번호 목록과 헷갈리지 않도록 순서 문제는 front matter에 `type: ordering`을 적어야 함
+    let mut ordering = false;
+    for (line_index, line) in contents.lines().enumerate() {
+        let line_number = line_index + 1;
+        if parsing_mode == ParseMode::FrontMatter {
+            if line == "---" {
+                parsing_mode = ParseMode::Title;
+            } else if let Some(value) = line.strip_prefix("id:") {
+                id = Some(value.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("type:") {
+                match value.trim() {
+                    "ordering" => ordering = true,
+                    other => diagnostics.push(Diagnostic::new(
+                        line_number,
+                        format!("type must be ordering, found: {}", other),
+                    )),
+                }
+            } else if let Some(value) = line.strip_prefix("match:") {
+                match MatchRule::parse(value.trim()) {
+                    Ok(v) => matching = v,
+                    Err(message) => diagnostics.push(Diagnostic::new(line_number, message)),
+                }
+            } else {
+                diagnostics.push(Diagnostic::new(
+                    line_number,
+                    format!("unknown front matter entry: {}", line),
+                ));
+            }
+            continue;
+        }
+        if line_index == 0 && line == "---" {
+            parsing_mode = ParseMode::FrontMatter;
+            continue;
+        }
+        if parsing_mode == ParseMode::Explanation {
+            explanation.push_str(line);
+            explanation.push('\n');
+            continue;
+        }
+
+        if line.is_empty() {
+            match parsing_mode {
+                ParseMode::Title | ParseMode::Description | ParseMode::Choices => continue,
+                _ => {}
+            }
+        } else if let Some(attributes) = line.strip_prefix("```rs") {
+            if parsing_mode == ParseMode::Code {
+                diagnostics.push(Diagnostic::new(
+                    line_number,
+                    "code block opened inside another code block",
+                ));
+            }
+            match CodeCheck::parse(attributes) {
+                Ok(v) => code_check = v,
+                Err(message) => diagnostics.push(Diagnostic::new(line_number, message)),
+            }
+            parsing_mode = ParseMode::Code;
+            code_start = line_number;
+            continue;
+        } else if line == "```" {
+            if parsing_mode != ParseMode::Code {
+                diagnostics.push(Diagnostic::new(
+                    line_number,
+                    "closing ``` without an opening ```rs",
+                ));
+            }
+            parsing_mode = ParseMode::Choices;
+            continue;
+        } else if (line.starts_with("- [") || (ordering && step_number(line).is_some()))
+            && matches!(parsing_mode, ParseMode::Description)
+        {
+            parsing_mode = ParseMode::Choices;
+        } else if line == "## 해설" && matches!(parsing_mode, ParseMode::Choices) {
+            parsing_mode = ParseMode::Explanation;
+            continue;
+        }
+
+        match parsing_mode {
+            ParseMode::Title => {
+                if let Some(text) = line.strip_prefix("# ") {
+                    title = Some(text.to_string());
+                } else {
+                    diagnostics.push(Diagnostic::new(
+                        line_number,
+                        format!("first line must be a title starting with \"# \", found: {}", line),
+                    ));
+                }
+                parsing_mode = ParseMode::Description;
+            }
+            ParseMode::Description => {
+                description.push_str(line);
+                description.push('\n');
+            }
+            ParseMode::Code => {
+                code.push_str(line);
+                code.push('\n');
+            }
+            ParseMode::Choices => {
+                if let Some((number, label)) = step_number(line).filter(|_| ordering) {
+                    // 순서 문제에는 다른 선택지가 없으므로 지금까지의 선택지 수가 곧 항목 수임
+                    let steps = choices.len();
+                    if number != steps + 1 {
+                        diagnostics.push(Diagnostic::new(
+                            line_number,
+                            format!("ordering steps must be numbered from 1 in order, expected {}. but found {}.", steps + 1, number),
+                        ));
+                    }
+                    if choices.iter().any(|v| matches!(v, ParsedChoice::Step { label: other } if other == label)) {
+                        diagnostics.push(Diagnostic::new(line_number, format!("duplicate ordering step: {}", label)));
+                    }
+                    // 번호 목록의 순서가 곧 정답 순서임
+                    answers.push(choices.len());
+                    choices.push(ParsedChoice::Step { label: label.to_string() });
+                    continue;
+                }
+                if ordering {
+                    diagnostics.push(Diagnostic::new(
+                        line_number,
+                        format!("ordering steps must be written as \"1. step\", found: {}", line),
+                    ));
+                    continue;
+                }
+
+                let pattern1 = "- [ ] ";
+                let pattern2 = "- [x] ";
+                assert_eq!(pattern1.len(), pattern2.len());
+                let is_answer = line.starts_with(pattern2);
+                if !is_answer && !line.starts_with(pattern1) {
+                    diagnostics.push(Diagnostic::new(
+                        line_number,
+                        format!("choices must start with \"- [ ] \" or \"- [x] \", found: {}", line),
+                    ));
+                    continue;
+                }
+
+                let choice = if line.ends_with(']') {
+                    let bracket_start_pattern = ": [";
+                    let Some(bracket_start) = line.rfind(bracket_start_pattern) else {
+                        diagnostics.push(Diagnostic::new(
+                            line_number,
+                            format!("subjective choices must be written as \"label: [answer]\", found: {}", line),
+                        ));
+                        continue;
+                    };
+                    let choice_label = &line[pattern1.len()..bracket_start];
+                    let text_start = bracket_start + bracket_start_pattern.len();
+                    let text_contents = &line[text_start..line.len() - 1];
+                    if text_contents.trim() == OUTPUT_PLACEHOLDER {
+                        if !is_answer {
+                            diagnostics.push(Diagnostic::new(line_number, "output answers must be marked with [x]"));
+                        }
+                        if code.is_empty() {
+                            diagnostics.push(Diagnostic::new(line_number, "output answers need a code block to run"));
+                        }
+                        if matching == MatchRule::Regex {
+                            diagnostics.push(Diagnostic::new(line_number, "output answers cannot be used with match: regex"));
+                        }
+                        if matches!(code_check, CodeCheck::CompileFail { .. }) {
+                            diagnostics.push(Diagnostic::new(
+                                line_number,
+                                "output answers cannot be used with a compile_fail code block",
+                            ));
+                        }
+                        answers.push(choices.len());
+                        choices.push(ParsedChoice::Output {
+                            label: choice_label.to_string(),
+                        });
+                        continue;
+                    }
+                    let values = split_values(text_contents, matching);
+                    if is_answer && values.is_empty() {
+                        diagnostics.push(Diagnostic::new(
+                            line_number,
+                            "subjective answers marked with [x] must list at least one accepted value",
+                        ));
+                    }
+                    for value in &values {
+                        if let Err(message) = matching.check(value) {
+                            diagnostics.push(Diagnostic::new(line_number, message));
+                        }
+                    }
+                    ParsedChoice::Subjective {
+                        label: choice_label.to_string(),
+                        values,
+                    }
+                } else if line.ends_with('}') && let Some(brace_start) = line.rfind(": {") {
+                    let choice_label = &line[pattern1.len()..brace_start];
+                    let contents = &line[brace_start + ": {".len()..line.len() - 1];
+                    match parse_numeric(contents) {
+                        Ok((value, tolerance, unit)) => ParsedChoice::Numeric {
+                            label: choice_label.to_string(),
+                            value,
+                            tolerance,
+                            unit,
+                        },
+                        Err(message) => {
+                            diagnostics.push(Diagnostic::new(line_number, message));
+                            continue;
+                        }
+                    }
+                } else {
+                    ParsedChoice::Choice {
+                        label: line[pattern1.len()..].to_string(),
+                    }
+                };
+                if is_answer {
+                    answers.push(choices.len());
+                }
+                choices.push(choice);
+            }
+            ParseMode::FrontMatter | ParseMode::Explanation => unreachable!(),
+        }
+    }
+
+    if parsing_mode == ParseMode::Code {
+        diagnostics.push(Diagnostic::new(code_start, "code block is never closed with ```"));
+    }
+    if parsing_mode == ParseMode::FrontMatter {
+        diagnostics.push(Diagnostic::new(1, "front matter is never closed with ---"));
+    }
+    // 제목 줄이 잘못된 경우는 이미 위에서 보고했음
+    if parsing_mode == ParseMode::Title {
+        diagnostics.push(Diagnostic {
+            line: None,
+            message: "file does not contain a title".to_string(),
+        });
+    }
+    if choices.is_empty() {
+        diagnostics.push(Diagnostic {
+            line: None,
+            message: "file does not contain any choices".to_string(),
+        });
+    } else if ordering && choices.len() == 1 {
+        diagnostics.push(Diagnostic {
+            line: None,
+            message: "ordering questions need at least two steps".to_string(),
+        });
+    } else if answers.is_empty() {
+        diagnostics.push(Diagnostic {
+            line: None,
+            message: "file does not contain an answer marked with [x]".to_string(),
+        });
+    }
+
+    match title {
+        Some(title) if diagnostics.is_empty() => Ok(ParsedQuestion {
+            id,
+            title,
+            description,
+            code,
+            code_check,
+            choices,
+            answers,
+            explanation: explanation.trim().to_string(),
+            matching,
+        }),
+        _ => Err(ParseError { diagnostics }),
+    }
+}
+
+/// 디렉토리 안의 모든 마크다운 파일을 분석해 id 순서로 정렬함.
+/// 한 파일에 오류가 있어도 나머지 파일을 계속 분석해 모든 오류를 모아서 반환함.
+pub fn load_directory(path: &Path) -> Result<Vec<QuestionFile>, Vec<LoadError>> {
+    let mut questions = vec![];
+    let mut errors = vec![];
+    let directory = match fs::read_dir(path) {
+        Ok(v) => v,
+        Err(e) => {
+            errors.push(LoadError {
+                file: None,
+                diagnostic: Diagnostic {
+                    line: None,
+                    message: format!("Unable to read directory: {}", e),
+                },
+            });
+            return Err(errors);
+        }
+    };
+
+    for question_file in directory {
+        let question_file = match question_file {
+            Ok(v) => v,
+            Err(e) => {
+                errors.push(LoadError {
+                    file: None,
+                    diagnostic: Diagnostic {
+                        line: None,
+                        message: format!("Unable to access file: {}", e),
+                    },
+                });
+                continue;
+            }
+        };
+        let file_name = question_file.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            // 문제 파일로 보이는 경우에만 오류로 보고하고, 그 외의 파일은 다른 파일처럼 무시함
+            let lossy_name = file_name.to_string_lossy();
+            if lossy_name.ends_with(".md") {
+                errors.push(LoadError::new(&lossy_name, None, "file name is not valid UTF-8".to_string()));
+            }
+            continue;
+        };
+        let Some(slug) = file_name.strip_suffix(".md") else {
+            continue;
+        };
+
+        let contents = match fs::read_to_string(question_file.path()) {
+            Ok(v) => v,
+            Err(e) => {
+                errors.push(LoadError::new(file_name, None, format!("Unable to read file: {}", e)));
+                continue;
+            }
+        };
+
+        let question = match parse_question(&contents) {
+            Ok(v) => v,
+            Err(e) => {
+                errors.extend(e.diagnostics.into_iter().map(|diagnostic| LoadError {
+                    file: Some(file_name.to_string()),
+                    diagnostic,
+                }));
+                continue;
+            }
+        };
+
+        // front matter에 id가 없으면 파일 이름을 id로 사용함
+        let id = question.id.clone().unwrap_or_else(|| slug.to_string());
+        if !is_valid_id(&id) {
+            errors.push(LoadError::new(
+                file_name,
+                None,
+                format!("question id \"{}\" may only contain ASCII letters, digits, '-' and '_'", id),
+            ));
+            continue;
+        }
+
+        questions.push(QuestionFile {
+            id,
+            file_name: file_name.to_string(),
+            question,
+        });
+    }
+
+    // read_dir의 순서는 파일 시스템마다 다르기 때문에 id 순서로 정렬함
+    questions.sort_by(|a, b| a.id.cmp(&b.id));
+    for pair in questions.windows(2) {
+        if pair[0].id == pair[1].id {
+            errors.push(LoadError::new(
+                pair[1].file_name.as_str(),
+                None,
+                format!("question id \"{}\" is already used by {}", pair[1].id, pair[0].file_name),
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(questions)
+    } else {
+        Err(errors)
+    }
+}
+
+/// `1. 항목` 형식의 줄이면 번호와 항목을 반환함
+fn step_number(line: &str) -> Option<(usize, &str)> {
+    let (number, label) = line.split_once(". ")?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((number.parse().ok()?, label.trim()))
+}
+
+/// `{값 ± 허용 오차 단위}`의 중괄호 안을 읽음. 허용 오차와 단위는 생략할 수 있고, `±` 대신 `+-`를 써도 됨.
+fn parse_numeric(contents: &str) -> Result<(f64, f64, String), String> {
+    let contents = contents.trim();
+    let (value, rest) = contents.split_once(char::is_whitespace).unwrap_or((contents, ""));
+    let Some(value) = value.parse::<f64>().ok().filter(|v| v.is_finite()) else {
+        return Err(format!("numeric answers must be written as \"label: {{value ± tolerance unit}}\", found: {{{}}}", contents));
+    };
+
+    let rest = rest.trim_start();
+    let Some(rest) = rest.strip_prefix('±').or_else(|| rest.strip_prefix("+-")) else {
+        return Ok((value, 0.0, rest.to_string()));
+    };
+    let rest = rest.trim_start();
+    let (tolerance, unit) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    match tolerance.parse::<f64>() {
+        Ok(tolerance) if tolerance.is_finite() && tolerance >= 0.0 => Ok((value, tolerance, unit.trim().to_string())),
+        _ => Err(format!("numeric tolerance must be a non-negative number, found: {}", tolerance)),
+    }
+}
+
+/// `|`로 구분한 서술형 답들을 나눔. 답 안의 `|`는 `\|`로 적음.
+/// 정규식에서는 `\|`가 원래 `|` 문자를 뜻하므로 그대로 둠.
+fn split_values(contents: &str, matching: MatchRule) -> Vec<String> {
+    let mut values = vec![];
+    let mut current = String::new();
+    let mut chars = contents.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('|') if matching != MatchRule::Regex => current.push('|'),
+                Some(next) => {
+                    current.push(c);
+                    current.push(next);
+                }
+                None => current.push(c),
+            },
+            '|' => values.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    values.push(current);
+
+    values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl CodeCheck {
+    /// ```` ```rs ```` 뒤에 적은 값을 읽음. 비어 있거나 `compile`, `compile_fail`, `compile_fail=오류 코드`, `output=값` 중 하나임.
+    /// 출력에 줄바꿈이 필요하면 `\n`으로 적음.
+    fn parse(attributes: &str) -> Result<CodeCheck, String> {
+        let attributes = attributes.trim();
+        if let Some(output) = attributes.strip_prefix("output=") {
+            return Ok(CodeCheck::Output(output.replace("\\n", "\n")));
+        }
+        if let Some(error) = attributes.strip_prefix("compile_fail=") {
+            let is_error_code = error.len() == 5
+                && error.starts_with('E')
+                && error[1..].chars().all(|c| c.is_ascii_digit());
+            return match is_error_code {
+                true => Ok(CodeCheck::CompileFail {
+                    error: Some(error.to_string()),
+                }),
+                false => Err(format!("compile_fail expects an error code like E0004, found: {}", error)),
+            };
+        }
+        match attributes {
+            "" => Ok(CodeCheck::None),
+            "compile" => Ok(CodeCheck::Compile),
+            "compile_fail" => Ok(CodeCheck::CompileFail { error: None }),
+            _ => Err(format!(
+                "code block must be annotated with compile, compile_fail or output=..., found: {}",
+                attributes
+            )),
+        }
+    }
+}
+
+impl MatchRule {
+    /// front matter의 `match:` 값을 읽음. `exact`, `whitespace`, `numeric`, `numeric 0.5`, `regex` 중 하나임.
+    fn parse(value: &str) -> Result<MatchRule, String> {
+        let mut words = value.split_whitespace();
+        let rule = match (words.next(), words.next()) {
+            (Some("exact"), None) => MatchRule::Exact,
+            (Some("whitespace"), None) => MatchRule::Whitespace,
+            (Some("numeric"), None) => MatchRule::Numeric { tolerance: 0.0 },
+            (Some("numeric"), Some(tolerance)) => match tolerance.parse::<f64>() {
+                Ok(tolerance) if tolerance.is_finite() && tolerance >= 0.0 => MatchRule::Numeric { tolerance },
+                _ => return Err(format!("numeric tolerance must be a non-negative number, found: {}", tolerance)),
+            },
+            (Some("regex"), None) => MatchRule::Regex,
+            _ => {
+                return Err(format!(
+                    "match must be one of exact, whitespace, numeric [tolerance] or regex, found: {}",
+                    value
+                ));
+            }
+        };
+        match words.next() {
+            None => Ok(rule),
+            Some(_) => Err(format!("unexpected words after match rule: {}", value)),
+        }
+    }
+
+    /// 정답으로 적은 값을 이 방법으로 비교할 수 있는지 확인함
+    fn check(&self, value: &str) -> Result<(), String> {
+        match self {
+            MatchRule::Exact | MatchRule::Whitespace => Ok(()),
+            MatchRule::Numeric { .. } => match value.parse::<f64>() {
+                Ok(v) if v.is_finite() => Ok(()),
+                _ => Err(format!("numeric answer is not a number: {}", value)),
+            },
+            MatchRule::Regex => match Regex::new(value) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("invalid regex {}: {}", value, e)),
+            },
+        }
+    }
+}
+
+impl Diagnostic {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Diagnostic {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl LoadError {
+    fn new(file: &str, line: Option<usize>, message: String) -> Self {
+        LoadError {
+            file: Some(file.to_string()),
+            diagnostic: Diagnostic { line, message },
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, self.diagnostic.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: {}", file, line, self.diagnostic.message),
+            (Some(file), None) => write!(f, "{}: {}", file, self.diagnostic.message),
+            (None, _) => write!(f, "{}", self.diagnostic.message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
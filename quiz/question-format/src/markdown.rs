@@ -0,0 +1,30 @@
+//! 문제 설명, 선택지, 해설에 적은 마크다운을 HTML로 바꿈.
+//! 문제 파일은 줄바꿈을 그대로 보여주는 것을 전제로 작성되어 있으므로 문단 안의 줄바꿈도 `<br />`로 바꿈.
+//! 마크다운 안의 HTML 태그는 태그로 해석하지 않고 글자 그대로 보여줌.
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, html};
+
+/// 여러 문단으로 이루어진 마크다운을 HTML로 바꿈
+pub fn render_markdown(text: &str) -> String {
+    let mut output = String::new();
+    html::push_html(&mut output, events(text));
+    output
+}
+
+/// 선택지처럼 한 줄짜리 마크다운을 `<p>`로 감싸지 않고 HTML로 바꿈
+pub fn render_inline_markdown(text: &str) -> String {
+    let mut output = String::new();
+    let events = events(text).filter(|v| !matches!(v, Event::Start(Tag::Paragraph) | Event::End(TagEnd::Paragraph)));
+    html::push_html(&mut output, events);
+    output.trim_end().to_string()
+}
+
+fn events(text: &str) -> impl Iterator<Item = Event<'_>> {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES;
+    Parser::new_ext(text, options).map(|event| match event {
+        Event::SoftBreak => Event::HardBreak,
+        // Vec<u8>처럼 꺾쇠를 쓴 글자가 태그로 해석되어 사라지지 않도록 글자로 취급함
+        Event::Html(v) | Event::InlineHtml(v) => Event::Text(v),
+        event => event,
+    })
+}
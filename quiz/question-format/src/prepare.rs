@@ -0,0 +1,93 @@
+//! 문제 파일을 서버에서 사용하는 모양으로 바꿈.
+//! include_questions! 매크로와 개발용 로더가 코드 블록 실행, 색칠, 선택지 변환을 같은 방식으로 하도록 한곳에 모아둠.
+//! 두 곳은 이 모듈이 만든 값을 각자의 타입으로 옮기기만 함.
+
+use crate::compile::expected_output;
+use crate::highlight::highlight_rust;
+use crate::{LoadError, MatchRule, ParsedChoice, QuestionFile, load_directory};
+use std::path::Path;
+
+/// 서버의 `Question`과 같은 모양으로 모든 값을 채운 문제
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedQuestion {
+    pub id: String,
+    /// 문제 폴더 기준 파일 이름
+    pub file: String,
+    pub name: String,
+    pub description: String,
+    pub code: String,
+    /// 색칠한 코드 블록 HTML. 코드 블록이 없으면 빈 문자열임.
+    pub code_html: String,
+    pub choices: Vec<PreparedChoice>,
+    /// 정답 선택지 번호들
+    pub answer: Vec<usize>,
+    pub explanation: String,
+    pub matching: MatchRule,
+}
+
+/// 서버의 `Answer`와 같은 모양의 선택지
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreparedChoice {
+    Choice { label: String },
+    Subjective { label: String, values: Vec<String> },
+    Numeric { label: String, value: f64, tolerance: f64, unit: String },
+    Step { label: String },
+    /// 코드 블록을 실행해 얻은 표준 출력을 정답으로 가짐
+    Output { label: String, output: String },
+}
+
+/// 디렉토리의 문제를 모두 읽고, 실행 결과를 정답으로 사용하는 문제는 코드 블록을 실제로 실행해 정답을 정함.
+/// 형식 오류와 실행 오류를 모두 모아서 반환함.
+pub fn prepare_directory(path: &Path) -> Result<Vec<PreparedQuestion>, Vec<LoadError>> {
+    let files = load_directory(path)?;
+
+    let mut errors = vec![];
+    let mut outputs = vec![];
+    for file in &files {
+        match expected_output(&file.question) {
+            Ok(v) => outputs.push(v),
+            Err(message) => errors.push(LoadError::new(&file.file_name, None, message)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(files.into_iter().zip(outputs).map(|(file, output)| prepare(file, output)).collect())
+}
+
+fn prepare(file: QuestionFile, output: Option<String>) -> PreparedQuestion {
+    let QuestionFile { id, file_name, question } = file;
+    let choices = question
+        .choices
+        .into_iter()
+        .map(|choice| match choice {
+            ParsedChoice::Choice { label } => PreparedChoice::Choice { label },
+            ParsedChoice::Subjective { label, values } => PreparedChoice::Subjective { label, values },
+            ParsedChoice::Numeric { label, value, tolerance, unit } => {
+                PreparedChoice::Numeric { label, value, tolerance, unit }
+            }
+            ParsedChoice::Step { label } => PreparedChoice::Step { label },
+            ParsedChoice::Output { label } => PreparedChoice::Output {
+                label,
+                output: output.clone().unwrap_or_default(),
+            },
+        })
+        .collect();
+
+    PreparedQuestion {
+        id,
+        file: file_name,
+        name: question.title,
+        description: question.description,
+        code_html: match question.code.is_empty() {
+            true => String::new(),
+            false => highlight_rust(&question.code),
+        },
+        code: question.code,
+        choices,
+        answer: question.answers,
+        explanation: question.explanation,
+        matching: question.matching,
+    }
+}
@@ -0,0 +1,366 @@
+use question_format::compile::{check_code, expected_output};
+use question_format::highlight::{highlight_rust, theme_css};
+use question_format::markdown::{render_inline_markdown, render_markdown};
+use question_format::{CodeCheck, MatchRule, ParsedChoice, load_directory, parse_question};
+use std::fs;
+use std::path::Path;
+
+const QUESTIONS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../questions");
+
+#[test]
+fn every_question_file_parses() {
+    let directory = Path::new(QUESTIONS_DIR);
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|v| v != "md") {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let question = match parse_question(&contents) {
+            Ok(v) => v,
+            Err(e) => panic!("{} failed to parse:\n{}", path.display(), e),
+        };
+
+        assert!(!question.title.trim().is_empty(), "{} has empty title", path.display());
+        assert!(!question.answers.is_empty(), "{} has no answer", path.display());
+        for &answer in &question.answers {
+            assert!(
+                answer < question.choices.len(),
+                "{} marks choice {} that does not exist",
+                path.display(),
+                answer
+            );
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn reports_non_utf8_file_names() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let directory = std::env::temp_dir().join(format!("question-format-names-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join(OsStr::from_bytes(b"bad\xff.md")), "# 제목\n- [x] 하나\n").unwrap();
+    fs::write(directory.join(OsStr::from_bytes(b"notes\xff.txt")), "").unwrap();
+    let result = load_directory(&directory);
+    fs::remove_dir_all(&directory).unwrap();
+
+    let Err(errors) = result else {
+        panic!("non UTF-8 question file must be reported");
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].diagnostic.message.contains("UTF-8"));
+}
+
+#[test]
+fn directory_is_sorted_by_unique_id() {
+    let questions = match load_directory(Path::new(QUESTIONS_DIR)) {
+        Ok(v) => v,
+        Err(errors) => panic!("{:#?}", errors),
+    };
+
+    assert!(!questions.is_empty());
+    for pair in questions.windows(2) {
+        assert!(pair[0].id < pair[1].id, "{} and {} are out of order", pair[0].id, pair[1].id);
+    }
+    for file in &questions {
+        assert_eq!(format!("{}.md", file.id), file.file_name);
+    }
+}
+
+#[test]
+fn parses_all_sections() {
+    let contents = "\
+---
+id: sample
+---
+# 제목
+
+설명 첫 줄
+설명 둘째 줄
+
+```rs
+fn main() {
+
+    println!(\"hi\");
+}
+```
+
+- [ ] 하나
+- [x] 둘
+- [x] 셋: [3]
+
+## 해설
+첫 문단
+
+- [ ] 해설 안의 목록은 선택지가 아님
+";
+    let question = parse_question(contents).unwrap();
+
+    assert_eq!(question.id.as_deref(), Some("sample"));
+    assert_eq!(question.title, "제목");
+    assert_eq!(question.description, "설명 첫 줄\n설명 둘째 줄\n");
+    assert_eq!(question.code, "fn main() {\n\n    println!(\"hi\");\n}\n");
+    assert_eq!(
+        question.choices,
+        vec![
+            ParsedChoice::Choice { label: "하나".to_string() },
+            ParsedChoice::Choice { label: "둘".to_string() },
+            ParsedChoice::Subjective {
+                label: "셋".to_string(),
+                values: vec!["3".to_string()]
+            },
+        ]
+    );
+    assert_eq!(question.answers, vec![1, 2]);
+    assert_eq!(question.explanation, "첫 문단\n\n- [ ] 해설 안의 목록은 선택지가 아님");
+    assert_eq!(question.matching, MatchRule::Exact);
+}
+
+#[test]
+fn parses_accepted_values_and_match_rules() {
+    let question = parse_question("---\nmatch: whitespace\n---\n# 제목\n- [x] 크기: [4 바이트 | 4|a\\|b ]\n- [ ] 기타: []\n").unwrap();
+    assert_eq!(question.matching, MatchRule::Whitespace);
+    assert_eq!(
+        question.choices,
+        vec![
+            ParsedChoice::Subjective {
+                label: "크기".to_string(),
+                values: vec!["4 바이트".to_string(), "4".to_string(), "a|b".to_string()]
+            },
+            ParsedChoice::Subjective {
+                label: "기타".to_string(),
+                values: vec![]
+            },
+        ]
+    );
+
+    let question = parse_question("---\nmatch: numeric 0.5\n---\n# 제목\n- [x] 값: [3.14]\n").unwrap();
+    assert_eq!(question.matching, MatchRule::Numeric { tolerance: 0.5 });
+
+    // 정규식에서는 \|를 그대로 둠
+    let question = parse_question("---\nmatch: regex\n---\n# 제목\n- [x] 값: [a\\|b|c+]\n").unwrap();
+    let ParsedChoice::Subjective { values, .. } = &question.choices[0] else {
+        panic!("expected a subjective choice");
+    };
+    assert_eq!(values, &["a\\|b", "c+"]);
+}
+
+#[test]
+fn reports_invalid_match_rules_and_values() {
+    let error = parse_question("---\nmatch: fuzzy\n---\n# 제목\n- [x] 값: [1]\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(2));
+
+    let error = parse_question("---\nmatch: numeric\n---\n# 제목\n- [x] 값: [열]\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(5));
+    assert!(error.diagnostics[0].message.contains("number"));
+
+    for rule in ["numeric inf", "numeric NaN", "numeric -1"] {
+        let error = parse_question(&format!("---\nmatch: {}\n---\n# 제목\n- [x] 값: [1]\n", rule)).unwrap_err();
+        assert!(error.diagnostics[0].message.contains("non-negative"), "{}", rule);
+    }
+
+    let error = parse_question("---\nmatch: numeric\n---\n# 제목\n- [x] 값: [NaN]\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(5));
+
+    let error = parse_question("---\nmatch: regex\n---\n# 제목\n- [x] 값: [(]\n").unwrap_err();
+    assert!(error.diagnostics[0].message.contains("regex"));
+
+    let error = parse_question("# 제목\n- [x] 값: []\n").unwrap_err();
+    assert!(error.diagnostics[0].message.contains("accepted value"));
+
+    for contents in ["NaN ± 1", "inf", "infinity ± 1", "3 ± inf", "3 ± NaN"] {
+        let error = parse_question(&format!("# 제목\n- [x] 값: {{{}}}\n", contents)).unwrap_err();
+        assert_eq!(error.diagnostics[0].line, Some(2), "{}", contents);
+    }
+}
+
+#[test]
+fn parses_numeric_answers() {
+    let question = parse_question("# 제목\n- [x] 크기: {4 ± 0.5 바이트}\n- [ ] 개수: {3}\n- [ ] 높이: {1.5 +- 0.1}\n").unwrap();
+    assert_eq!(
+        question.choices,
+        vec![
+            ParsedChoice::Numeric {
+                label: "크기".to_string(),
+                value: 4.0,
+                tolerance: 0.5,
+                unit: "바이트".to_string()
+            },
+            ParsedChoice::Numeric {
+                label: "개수".to_string(),
+                value: 3.0,
+                tolerance: 0.0,
+                unit: String::new()
+            },
+            ParsedChoice::Numeric {
+                label: "높이".to_string(),
+                value: 1.5,
+                tolerance: 0.1,
+                unit: String::new()
+            },
+        ]
+    );
+
+    let error = parse_question("# 제목\n- [x] 크기: {네 바이트}\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(2));
+    let error = parse_question("# 제목\n- [x] 크기: {4 ± -1}\n").unwrap_err();
+    assert!(error.diagnostics[0].message.contains("tolerance"));
+}
+
+#[test]
+fn parses_ordering_steps() {
+    let question = parse_question("---\ntype: ordering\n---\n# 제목\n설명\n\n1. 첫째\n2. 둘째\n3. 셋째\n").unwrap();
+    assert_eq!(question.description, "설명\n");
+    assert_eq!(
+        question.choices,
+        vec![
+            ParsedChoice::Step { label: "첫째".to_string() },
+            ParsedChoice::Step { label: "둘째".to_string() },
+            ParsedChoice::Step { label: "셋째".to_string() },
+        ]
+    );
+    assert_eq!(question.answers, vec![0, 1, 2]);
+
+    let ordering = |body: &str| parse_question(&format!("---\ntype: ordering\n---\n{}", body));
+    let error = ordering("# 제목\n1. 첫째\n3. 셋째\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(6));
+    let error = ordering("# 제목\n1. 첫째\n- [x] 둘째\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(6));
+    let error = ordering("# 제목\n1. 첫째\n").unwrap_err();
+    assert!(error.diagnostics[0].message.contains("two steps"));
+    let error = parse_question("---\ntype: sorting\n---\n# 제목\n- [x] 답\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(2));
+}
+
+#[test]
+fn numbered_list_in_description_is_not_ordering() {
+    let question = parse_question("# 제목\n다음 순서로 실행함.\n\n1. 컴파일\n2. 실행\n\n- [x] 맞다\n- [ ] 아니다\n").unwrap();
+    assert_eq!(question.description, "다음 순서로 실행함.\n1. 컴파일\n2. 실행\n");
+    assert_eq!(
+        question.choices,
+        vec![
+            ParsedChoice::Choice { label: "맞다".to_string() },
+            ParsedChoice::Choice { label: "아니다".to_string() },
+        ]
+    );
+}
+
+#[test]
+fn parses_code_block_annotations() {
+    let parse = |line: &str| parse_question(&format!("# 제목\n{}\nfn main() {{}}\n```\n- [x] 답\n", line));
+    assert_eq!(parse("```rs").unwrap().code_check, CodeCheck::None);
+    assert_eq!(parse("```rs compile").unwrap().code_check, CodeCheck::Compile);
+    assert_eq!(parse("```rs compile_fail").unwrap().code_check, CodeCheck::CompileFail { error: None });
+    assert_eq!(
+        parse("```rs compile_fail=E0004").unwrap().code_check,
+        CodeCheck::CompileFail {
+            error: Some("E0004".to_string())
+        }
+    );
+    assert_eq!(
+        parse("```rs output=a b\\nc").unwrap().code_check,
+        CodeCheck::Output("a b\nc".to_string())
+    );
+
+    assert_eq!(parse("```rs ignore").unwrap_err().diagnostics[0].line, Some(2));
+    assert!(parse("```rs compile_fail=4").is_err());
+}
+
+#[test]
+fn code_blocks_behave_as_annotated() {
+    let questions = load_directory(Path::new(QUESTIONS_DIR)).unwrap();
+    for file in questions {
+        if let Err(e) = check_code(&file.question.code, &file.question.code_check) {
+            panic!("{}: {}", file.file_name, e);
+        }
+    }
+
+    let code = "fn main() {\n    let x: i32 = \"1\";\n}\n";
+    assert!(check_code(code, &CodeCheck::Compile).is_err());
+    assert!(check_code(code, &CodeCheck::CompileFail { error: None }).is_ok());
+    let wrong_error = CodeCheck::CompileFail {
+        error: Some("E0004".to_string()),
+    };
+    assert!(check_code(code, &wrong_error).is_err());
+    let code = "fn main() {\n    println!(\"{}\", 1 + 2);\n}\n";
+    assert!(check_code(code, &CodeCheck::Output("3".to_string())).is_ok());
+    assert!(check_code(code, &CodeCheck::Output("4".to_string())).is_err());
+}
+
+#[test]
+fn output_answers_run_the_code_block() {
+    let question = parse_question("# 제목\n```rs\nfn main() {\n    println!(\"{}  \", 1 + 2);\n}\n```\n- [x] 출력: [@output]\n").unwrap();
+    assert_eq!(question.choices, vec![ParsedChoice::Output { label: "출력".to_string() }]);
+    assert_eq!(question.answers, vec![0]);
+    assert_eq!(expected_output(&question).unwrap().as_deref(), Some("3"));
+
+    let question = parse_question("# 제목\n- [x] 답: [3]\n").unwrap();
+    assert_eq!(expected_output(&question).unwrap(), None);
+
+    let error = parse_question("# 제목\n- [x] 출력: [@output]\n").unwrap_err();
+    assert!(error.diagnostics[0].message.contains("code block"));
+    let error = parse_question("# 제목\n```rs compile_fail\nfn main() {}\n```\n- [ ] 출력: [@output]\n").unwrap_err();
+    assert_eq!(error.diagnostics.len(), 2);
+}
+
+#[test]
+fn highlights_and_escapes_code() {
+    let html = highlight_rust("fn main() {\n    let v: Vec<u8> = vec![]; // 주석\n}\n");
+    assert!(html.contains("Vec<span class=\"hl-punctuation hl-definition hl-generic hl-begin hl-rust\">&lt;</span>"));
+    assert!(html.contains("<span class=\"hl-storage hl-type hl-function hl-rust\">fn</span>"));
+    assert!(html.contains("주석"));
+    assert!(!html.contains("<u8>"));
+}
+
+#[test]
+fn highlight_css_is_up_to_date() {
+    let css = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../highlight.css")).unwrap();
+    assert!(css == theme_css(), "highlight.css is outdated, regenerate it with question_format::highlight::theme_css()");
+}
+
+#[test]
+fn renders_markdown() {
+    assert_eq!(
+        render_markdown("**굵게** `&mut str`\n다음 줄\n\n- 목록"),
+        "<p><strong>굵게</strong> <code>&amp;mut str</code><br />\n다음 줄</p>\n<ul>\n<li>목록</li>\n</ul>\n"
+    );
+    assert_eq!(render_inline_markdown("`Vec<u8>`을 쓴다."), "<code>Vec&lt;u8&gt;</code>을 쓴다.");
+    // HTML 태그는 글자 그대로 보여줌
+    assert_eq!(render_inline_markdown("Vec<u8> <b>x</b>"), "Vec&lt;u8&gt; &lt;b&gt;x&lt;/b&gt;");
+    assert_eq!(render_inline_markdown("[링크](https://www.rust-lang.org)"), "<a href=\"https://www.rust-lang.org\">링크</a>");
+}
+
+#[test]
+fn collects_every_error_with_line_numbers() {
+    let contents = "\
+title
+
+```rs
+fn main() {}
+```
+* 잘못된 선택지
+- [ ] 라벨: 대괄호 없음]
+";
+    let error = parse_question(contents).unwrap_err();
+    let lines: Vec<_> = error.diagnostics.iter().map(|v| v.line).collect();
+
+    assert_eq!(lines, vec![Some(1), Some(6), Some(7), None]);
+    assert!(error.diagnostics[3].message.contains("choices"));
+}
+
+#[test]
+fn reports_missing_answer_and_unclosed_blocks() {
+    let error = parse_question("# 제목\n\n- [ ] 하나\n").unwrap_err();
+    assert_eq!(error.diagnostics.len(), 1);
+    assert!(error.diagnostics[0].message.contains("[x]"));
+
+    let error = parse_question("# 제목\n```rs\nfn main() {}\n").unwrap_err();
+    assert_eq!(error.diagnostics[0].line, Some(2));
+
+    let error = parse_question("---\nid: a\n").unwrap_err();
+    assert!(error.diagnostics.iter().any(|v| v.message.contains("front matter")));
+}
@@ -1,7 +1,8 @@
 use proc_macro::{Span, TokenStream};
+use question_format::MatchRule;
+use question_format::prepare::{PreparedChoice, PreparedQuestion, prepare_directory};
 use quote::quote;
-use std::fs;
-use std::io::Read;
+use std::path::PathBuf;
 
 #[proc_macro]
 pub fn include_questions(path: TokenStream) -> TokenStream {
@@ -9,153 +10,83 @@ pub fn include_questions(path: TokenStream) -> TokenStream {
     let span = Span::call_site();
     const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 
-    let location = format!("{}/../{}", MANIFEST_DIR, path.value());
+    let location = PathBuf::from(format!("{}/../{}", MANIFEST_DIR, path.value()));
 
-    let mut struct_initializations: Vec<proc_macro2::TokenStream> = vec![];
-    for question_file in fs::read_dir(location).expect("Unable to read directory") {
-        let question_file = question_file.expect("Unable to access file");
-        let file_name = question_file.file_name();
-        let Some(file_name) = file_name.to_str() else {
-            eprintln!("Invalid file name detected: {:?}", file_name);
-            continue;
-        };
-        if !file_name.ends_with(".md") {
-            continue;
+    // 실행 결과를 정답으로 사용하는 문제는 코드 블록을 실제로 실행해 정답을 정함
+    let questions = match prepare_directory(&location) {
+        Ok(v) => v,
+        Err(errors) => {
+            let errors: Vec<String> = errors
+                .into_iter()
+                .map(|e| match e.file {
+                    Some(_) => format!("{}/{}", path.value(), e),
+                    None => format!("{}: {}", path.value(), e),
+                })
+                .collect();
+            return compile_errors(&errors);
         }
+    };
 
-        let contents = {
-            let mut file = fs::File::open(question_file.path()).expect("Unable to open file");
-            let mut buffer = String::new();
-            file.read_to_string(&mut buffer)
-                .expect("Unable to read file");
-            buffer
+    let struct_initializations = questions.into_iter().map(|question| {
+        let PreparedQuestion { id, file, name, description, code, code_html, answer, explanation, .. } = &question;
+        let matching = match question.matching {
+            MatchRule::Exact => quote! { Matching::Exact },
+            MatchRule::Whitespace => quote! { Matching::Whitespace },
+            MatchRule::Numeric { tolerance } => quote! { Matching::Numeric { tolerance: #tolerance } },
+            MatchRule::Regex => quote! { Matching::Regex },
         };
 
-        let mut title = None;
-        let mut parsing_mode = ParseMode::Title;
-        let mut description = String::new();
-        let mut code = String::new();
-        let mut choices = vec![];
-        let mut answer = None;
-        for line in contents.lines() {
-            if line.is_empty() {
-                match parsing_mode {
-                    ParseMode::Title | ParseMode::Description | ParseMode::Choices => continue,
-                    _ => {}
-                }
-            } else if line.starts_with("```rs") {
-                parsing_mode = ParseMode::Code;
-                continue;
-            } else if line == "```" {
-                parsing_mode = ParseMode::Choices;
-                continue;
-            } else if line.starts_with("- [") && matches!(parsing_mode, ParseMode::Description) {
-                parsing_mode = ParseMode::Choices;
-            }
-
-            match parsing_mode {
-                ParseMode::Title => {
-                    if line.starts_with("# ") {
-                        title = Some(line[2..].to_string());
-                        parsing_mode = ParseMode::Description;
-                    } else {
-                        panic!("File {} has invalid title!:\n{}", file_name, line);
-                    }
-                }
-                ParseMode::Description => {
-                    description.push_str(line);
-                    description.push('\n');
-                }
-                ParseMode::Code => {
-                    code.push_str(line);
-                    code.push('\n');
-                }
-                ParseMode::Choices => {
-                    let pattern1 = "- [ ] ";
-                    let pattern2 = "- [x] ";
-                    assert_eq!(pattern1.len(), pattern2.len());
-                    if let None = line.find(pattern1).or_else(|| {
-                        if answer.is_some() {
-                            panic!("File {} contains duplicate x markers!", file_name);
-                        }
-                        answer = Some(choices.len());
-                        line.find(pattern2)
-                    }) {
-                        panic!("File {} contains invalid line: {}", file_name, line);
-                    }
-
-                    let bracket_end = line.rfind(']').unwrap();
-                    let choice = if bracket_end == line.len() - 1 {
-                        let bracket_start_pattern = ": [";
-                        let bracket_start = line
-                            .rfind(bracket_start_pattern)
-                            .expect(format!("File {} contains line: {}", file_name, line).as_str());
-                        let choice_label = &line[pattern1.len()..bracket_start];
-                        let text_start = bracket_start + bracket_start_pattern.len();
-                        let text_contents = &line[text_start..bracket_end];
-                        ChoiceKind::Subjective(choice_label.to_string(), text_contents.to_string())
-                    } else {
-                        ChoiceKind::Choice(line[pattern1.len()..].to_string())
-                    };
-                    choices.push(choice);
+        let choices = question.choices.iter().map(|choice| match choice {
+            PreparedChoice::Choice { label } => quote! {
+                Answer::Choice { label: #label }
+            },
+            PreparedChoice::Output { label, output } => quote! {
+                Answer::Output { label: #label, output: #output }
+            },
+            PreparedChoice::Step { label } => quote! {
+                Answer::Step { label: #label }
+            },
+            PreparedChoice::Numeric { label, value, tolerance, unit } => quote! {
+                Answer::Numeric { label: #label, value: #value, tolerance: #tolerance, unit: #unit }
+            },
+            PreparedChoice::Subjective { label, values } => {
+                let literals = values.iter().map(|v| syn::LitStr::new(v, span.into()));
+                quote! {
+                    Answer::Subjective { label: #label, values: &[#(#literals),*] }
                 }
             }
-        }
-
-        let Some(title) = title else {
-            panic!("File {} does not contain title!", file_name);
-        };
-        let Some(answer) = answer else {
-            panic!("File {} does not contain answer with x mark!", file_name);
-        };
-
-        let choices: Vec<proc_macro2::TokenStream> = choices
-            .into_iter()
-            .map(|choice| match choice {
-                ChoiceKind::Choice(label) => {
-                    quote! {
-                        Answer::Choice { label: #label }
-                    }
-                }
-                ChoiceKind::Subjective(label, text) => {
-                    let literal = syn::LitStr::new(&text, span.into());
-                    quote! {
-                        Answer::Subjective { label: #label, value: #literal }
-                    }
-                }
-            })
-            .collect();
-        let initializer = quote! {
+        });
+        quote! {
             Question {
-                name: #title,
+                id: #id,
+                file: #file,
+                name: #name,
                 description: #description,
                 code: #code,
+                code_html: #code_html,
                 choices: &[
                     #(#choices),*
                 ],
-                answer: #answer
+                answer: &[#(#answer),*],
+                explanation: #explanation,
+                matching: #matching
             }
-        };
-
-        struct_initializations.push(initializer);
-    }
+        }
+    });
 
-    let output = quote! {
+    quote! {
         &[#(#struct_initializations),*]
     }
-    .into();
-    output
-}
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum ParseMode {
-    Title,
-    Description,
-    Code,
-    Choices,
+    .into()
 }
 
-enum ChoiceKind {
-    Choice(String),       // 객관식
-    Subjective(String, String), // 주관식
+/// 발견한 모든 문제를 한 번에 보여주기 위해 compile_error!를 여러 개 만듦
+fn compile_errors(errors: &[String]) -> TokenStream {
+    quote! {
+        {
+            #(::core::compile_error!(#errors);)*
+            &[]
+        }
+    }
+    .into()
 }
@@ -0,0 +1,287 @@
+//! 관리자 페이지. 설정에 등록된 디스코드 사용자만 로그인해 제출 기록을 보고, 잘못된 제출을 지우거나 무효 처리할 수 있음.
+
+use crate::item_analysis::analyze;
+use crate::leaderboard::{Leaderboard, LeaderboardUpdate};
+use crate::submission_store::{StoreError, Submission};
+use crate::{DiscordGuildMember, OauthPurpose, ServiceState, cookie_id, find_question, random_u128, render_template};
+use askama::Template;
+use axum::body::Body;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tokio::task::spawn_blocking;
+use tracing::{error, info, warn};
+
+static ADMIN_COOKIE: &str = "adminSession";
+
+#[derive(Template)]
+#[template(path = "admin_template.html")]
+struct AdminPage<'a> {
+    title: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "admin_items_template.html")]
+struct ItemsPage<'a> {
+    title: &'a str,
+}
+
+/// 디스코드로 로그인한 관리자
+pub struct AdminSession {
+    pub user_id: String,
+    pub name: String,
+    pub started: Instant,
+}
+
+/// 제출 기록 목록에 표시하는 요약
+#[derive(Serialize, Debug)]
+pub struct SubmissionSummary {
+    id: String,
+    member_id: String,
+    username: String,
+    submitted_at: DateTime<Local>,
+    score: f32,
+    invalidated: bool,
+}
+
+/// 제출 하나의 문제별 답안
+#[derive(Serialize, Debug)]
+pub struct SubmissionDetail {
+    #[serde(flatten)]
+    summary: SubmissionSummary,
+    answers: Vec<AnswerDetail>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnswerDetail {
+    question: String,
+    /// 지금 문제 목록에 없는 문제이면 None
+    name: Option<&'static str>,
+    answer: Vec<String>,
+    correct: bool,
+    credit: f32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InvalidateRequest {
+    invalidated: bool,
+}
+
+/// 관리자 페이지. 로그인하지 않았으면 로그인 페이지로 보냄.
+pub async fn page(state: &ServiceState, headers: &HeaderMap) -> Response {
+    let page = AdminPage { title: &state.config.title };
+    render_page(state, headers, &page).await
+}
+
+/// 문항 분석 페이지
+pub async fn items_page(state: &ServiceState, headers: &HeaderMap) -> Response {
+    let page = ItemsPage { title: &state.config.title };
+    render_page(state, headers, &page).await
+}
+
+async fn render_page(state: &ServiceState, headers: &HeaderMap, page: &impl Template) -> Response {
+    if state.discord_data.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if admin_name(state, headers).await.is_none() {
+        return redirect("/admin/login");
+    }
+    render_template(page).into_response()
+}
+
+/// 디스코드 로그인 페이지로 보냄
+pub async fn login(state: &ServiceState) -> Response {
+    let Some(discord) = state.discord_data.as_ref() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    redirect(&discord.authorize_url(OauthPurpose::AdminLogin).await)
+}
+
+/// 디스코드 로그인을 마친 사용자가 관리자이면 세션을 만듦
+pub(crate) async fn complete_login(state: &ServiceState, member: DiscordGuildMember) -> Response {
+    if !state.config.admin.ids.contains(&member.user.id) {
+        warn!("Rejected admin login of \"{}\" ({})", member.name(), member.user.id);
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    info!("Admin \"{}\" ({}) logged in", member.name(), member.user.id);
+    let id = random_u128();
+    let session = AdminSession {
+        name: member.name().to_string(),
+        user_id: member.user.id,
+        started: Instant::now(),
+    };
+    state.admin_sessions.write().await.insert(id, session);
+
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, "/admin")
+        .header(
+            header::SET_COOKIE,
+            format!("{}={:X}; Path=/admin; HttpOnly; SameSite=Lax", ADMIN_COOKIE, id),
+        )
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// 모든 제출 기록을 최근에 제출한 순서로 반환함. 무효 처리한 기록도 포함됨.
+pub async fn list_submissions(state: &ServiceState, headers: &HeaderMap) -> Response {
+    if admin_name(state, headers).await.is_none() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let store = state.submission_store.clone();
+    match spawn_blocking(move || store.submissions()).await.unwrap() {
+        Ok(submissions) => {
+            let summaries: Vec<SubmissionSummary> = submissions.into_iter().rev().map(SubmissionSummary::from).collect();
+            Json(summaries).into_response()
+        }
+        Err(e) => store_error(e),
+    }
+}
+
+pub async fn get_submission(state: &ServiceState, headers: &HeaderMap, id: String) -> Response {
+    if admin_name(state, headers).await.is_none() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let store = state.submission_store.clone();
+    let submission = match spawn_blocking(move || store.get(&id)).await.unwrap() {
+        Ok(Some(v)) => v,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => return store_error(e),
+    };
+
+    let questions = state.question_set().await.questions;
+    let answers = submission
+        .result
+        .answers
+        .iter()
+        .map(|v| AnswerDetail {
+            question: v.question.clone(),
+            name: find_question(questions, &v.question).map(|i| questions[i].name),
+            answer: v.answer.clone(),
+            correct: v.correct,
+            credit: v.credit,
+        })
+        .collect();
+
+    Json(SubmissionDetail {
+        summary: SubmissionSummary::from(submission),
+        answers,
+    })
+    .into_response()
+}
+
+/// 멤버별 최신 제출 기록으로 지금 문제 목록을 분석함
+pub async fn item_report(state: &ServiceState, headers: &HeaderMap) -> Response {
+    if admin_name(state, headers).await.is_none() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let store = state.submission_store.clone();
+    let submissions = match spawn_blocking(move || store.latest_per_member()).await.unwrap() {
+        Ok(v) => v,
+        Err(e) => return store_error(e),
+    };
+    let questions = state.question_set().await.questions;
+    Json(analyze(&submissions, questions)).into_response()
+}
+
+pub async fn delete_submission(state: &ServiceState, headers: &HeaderMap, id: String) -> Response {
+    let Some(admin) = admin_name(state, headers).await else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    let store = state.submission_store.clone();
+    let target = id.clone();
+    match spawn_blocking(move || store.delete(&target)).await.unwrap() {
+        Ok(true) => {}
+        Ok(false) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => return store_error(e),
+    }
+
+    info!("Admin \"{}\" deleted submission {}", admin, id);
+    reload_leaderboard(state).await;
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn set_invalidated(
+    state: &ServiceState,
+    headers: &HeaderMap,
+    id: String,
+    request: InvalidateRequest,
+) -> Response {
+    let Some(admin) = admin_name(state, headers).await else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    let store = state.submission_store.clone();
+    let target = id.clone();
+    let invalidated = request.invalidated;
+    match spawn_blocking(move || store.set_invalidated(&target, invalidated)).await.unwrap() {
+        Ok(true) => {}
+        Ok(false) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => return store_error(e),
+    }
+
+    match invalidated {
+        true => info!("Admin \"{}\" invalidated submission {}", admin, id),
+        false => info!("Admin \"{}\" restored submission {}", admin, id),
+    }
+    reload_leaderboard(state).await;
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// 제출 기록이 바뀌었으므로 저장소에서 순위표를 다시 만들고 순위 화면에 알림
+async fn reload_leaderboard(state: &ServiceState) {
+    match Leaderboard::load(state.submission_store.clone()).await {
+        Ok(leaderboard) => {
+            *state.leaderboard.write().await = leaderboard;
+            let _ = state.leaderboard_updates.send(LeaderboardUpdate::Reset);
+        }
+        Err(e) => error!("Unable to reload the leaderboard: {}", e),
+    }
+}
+
+/// 로그인한 관리자의 이름. 세션이 없거나 더 이상 관리자가 아니면 None을 반환함.
+async fn admin_name(state: &ServiceState, headers: &HeaderMap) -> Option<String> {
+    let id = cookie_id(headers, ADMIN_COOKIE)?;
+    let sessions = state.admin_sessions.read().await;
+    let session = sessions.get(&id)?;
+    state
+        .config
+        .admin
+        .ids
+        .contains(&session.user_id)
+        .then(|| session.name.clone())
+}
+
+fn redirect(location: &str) -> Response {
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn store_error(e: StoreError) -> Response {
+    error!("Unable to access submission store: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+impl From<Submission> for SubmissionSummary {
+    fn from(submission: Submission) -> Self {
+        SubmissionSummary {
+            id: submission.id,
+            member_id: submission.member_id,
+            username: submission.username,
+            submitted_at: submission.submitted_at,
+            score: submission.result.score,
+            invalidated: submission.invalidated,
+        }
+    }
+}
@@ -0,0 +1,538 @@
+//! 강사가 진행하는 실시간 퀴즈 모드.
+//! 강사가 제어 페이지에서 문제를 넘기면 WebSocket으로 연결된 모든 참가자에게 같은 문제가 표시되고,
+//! 강사가 문제를 닫으면 답안이 잠기고 선택지별 응답 분포와 정답이 공개됨.
+
+use crate::{Answer, Question, ServiceState, SubmittedAnswer, SubmittedEntry, cookie_id, random_u128, render_template};
+use askama::Template;
+use axum::body::Body;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use question_format::markdown::{render_inline_markdown, render_markdown};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::error;
+
+static CLASSROOM_COOKIE: &str = "classroomParticipant";
+static LECTURER_COOKIE: &str = "classroomLecturer";
+
+#[derive(Template)]
+#[template(path = "classroom_template.html")]
+struct ParticipantPage<'a> {
+    title: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "classroom_control_template.html")]
+struct ControlPage<'a> {
+    title: &'a str,
+    /// false이면 강사 키를 입력하는 칸만 보여줌
+    authorized: bool,
+    /// 잘못된 강사 키를 입력했는지 여부
+    rejected: bool,
+}
+
+pub struct Classroom {
+    stage: RwLock<Stage>,
+    events: broadcast::Sender<ClassroomEvent>,
+    /// 서버가 발급한 참가자 id -> 마지막으로 사용한 시간.
+    /// 쿠키를 마음대로 바꿔 여러 참가자인 척할 수 없도록 발급한 id만 받음.
+    participants: RwLock<HashMap<u128, Instant>>,
+    /// 강사 키를 확인한 뒤 발급한 강사 id -> 마지막으로 사용한 시간
+    lecturers: RwLock<HashMap<u128, Instant>>,
+}
+
+enum Stage {
+    /// 강사가 아직 첫 문제를 열지 않음
+    Waiting,
+    Question(Round),
+    Finished,
+}
+
+/// 지금 진행 중인 문제 하나
+struct Round {
+    index: usize,
+    count: usize,
+    question: &'static Question,
+    /// false이면 답안이 잠기고 정답이 공개됨
+    open: bool,
+    /// 선택지를 표시할 순서. 다시 연결해도 같은 순서로 보이도록 문제를 열 때 정함.
+    order: Vec<usize>,
+    /// 참가자 id -> (제출한 선택지, 정답 여부)
+    answers: HashMap<u128, (Vec<SubmittedEntry>, bool)>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Role {
+    Lecturer,
+    Participant(u128),
+}
+
+/// 모든 연결에 뿌리는 메시지. 응답 분포처럼 강사에게만 보여야 하는 메시지가 있어 받는 사람을 함께 보냄.
+#[derive(Clone)]
+struct ClassroomEvent {
+    lecturer_only: bool,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    Waiting,
+    Question {
+        index: usize,
+        count: usize,
+        open: bool,
+        question: QuestionView<'a>,
+    },
+    /// 강사에게만 보내는 실시간 응답 분포
+    Distribution { distribution: Distribution },
+    /// 문제가 닫힌 뒤 모두에게 공개하는 응답 분포와 정답
+    Closed {
+        distribution: Distribution,
+        answer: &'a [usize],
+        /// HTML로 바꾼 해설
+        explanation_html: String,
+    },
+    /// 참가자 자신이 제출한 답안
+    Answered { submitted: &'a [SubmittedEntry] },
+    Rejected { reason: &'a str },
+    Finished,
+}
+
+/// 참가자에게 보여주는 문제. 정답은 포함하지 않음.
+#[derive(Serialize)]
+struct QuestionView<'a> {
+    id: &'a str,
+    name: &'a str,
+    /// HTML로 바꾼 문제 설명
+    description_html: String,
+    /// 색칠한 코드 블록 HTML
+    code_html: &'a str,
+    choices: Vec<ChoiceView<'a>>,
+    /// 정답이 여러 개라서 여러 선택지를 고를 수 있는지 여부
+    multiple: bool,
+    /// 항목을 순서대로 나열하는 문제인지 여부
+    ordering: bool,
+}
+
+#[derive(Serialize)]
+struct ChoiceView<'a> {
+    /// 문제 파일에 적힌 선택지 번호. 답안을 보낼 때 사용함.
+    index: usize,
+    label: &'a str,
+    /// HTML로 바꾼 라벨
+    label_html: String,
+    /// 답을 직접 입력하는 선택지인지 여부
+    subjective: bool,
+    /// 숫자만 입력하는 선택지인지 여부
+    numeric: bool,
+    /// 여러 줄을 입력하는 선택지인지 여부
+    multiline: bool,
+    unit: &'a str,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Distribution {
+    /// 선택지별로 고른 참가자 수. 순서 문제는 항목을 정답 위치에 놓은 참가자 수임.
+    counts: Vec<usize>,
+    /// 답안을 제출한 참가자 수
+    responses: usize,
+    /// 정답을 맞힌 참가자 수
+    correct: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LecturerCommand {
+    Show { index: usize },
+    Close,
+    Finish,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ParticipantCommand {
+    Answer { submitted: Vec<SubmittedEntry> },
+}
+
+impl Default for Classroom {
+    fn default() -> Self {
+        Classroom {
+            stage: RwLock::new(Stage::Waiting),
+            events: broadcast::channel(64).0,
+            participants: Default::default(),
+            lecturers: Default::default(),
+        }
+    }
+}
+
+impl Round {
+    fn distribution(&self) -> Distribution {
+        let mut counts = vec![0; self.question.choices.len()];
+        let mut correct = 0;
+        let ordering = self.question.is_ordering();
+        for (entries, is_correct) in self.answers.values() {
+            for (position, entry) in entries.iter().enumerate() {
+                if !ordering || self.question.answer.get(position) == Some(&entry.choice) {
+                    counts[entry.choice] += 1;
+                }
+            }
+            if *is_correct {
+                correct += 1;
+            }
+        }
+        Distribution {
+            counts,
+            responses: self.answers.len(),
+            correct,
+        }
+    }
+
+    fn question_message(&self) -> String {
+        let question = self.question;
+        let choices = self
+            .order
+            .iter()
+            .map(|&index| match &question.choices[index] {
+                Answer::Choice { label } | Answer::Step { label } => ChoiceView {
+                    index,
+                    label,
+                    label_html: render_inline_markdown(label),
+                    subjective: false,
+                    numeric: false,
+                    multiline: false,
+                    unit: "",
+                },
+                Answer::Subjective { label, .. } => ChoiceView {
+                    index,
+                    label,
+                    label_html: render_inline_markdown(label),
+                    subjective: true,
+                    numeric: false,
+                    multiline: false,
+                    unit: "",
+                },
+                Answer::Output { label, .. } => ChoiceView {
+                    index,
+                    label,
+                    label_html: render_inline_markdown(label),
+                    subjective: true,
+                    numeric: false,
+                    multiline: true,
+                    unit: "",
+                },
+                Answer::Numeric { label, unit, .. } => ChoiceView {
+                    index,
+                    label,
+                    label_html: render_inline_markdown(label),
+                    subjective: true,
+                    numeric: true,
+                    multiline: false,
+                    unit,
+                },
+            })
+            .collect();
+        encode(&ServerMessage::Question {
+            index: self.index,
+            count: self.count,
+            open: self.open,
+            question: QuestionView {
+                id: question.id,
+                name: question.name,
+                description_html: render_markdown(question.description),
+                code_html: question.code_html,
+                choices,
+                multiple: question.answer.len() > 1,
+                ordering: question.is_ordering(),
+            },
+        })
+    }
+
+    fn distribution_message(&self) -> String {
+        encode(&ServerMessage::Distribution {
+            distribution: self.distribution(),
+        })
+    }
+
+    fn closed_message(&self) -> String {
+        encode(&ServerMessage::Closed {
+            distribution: self.distribution(),
+            answer: self.question.answer,
+            explanation_html: render_markdown(self.question.explanation),
+        })
+    }
+}
+
+impl Classroom {
+    /// 쿠키의 참가자 id가 서버가 발급한 것이면 반환함
+    async fn participant_id(&self, headers: &HeaderMap) -> Option<u128> {
+        let id = cookie_id(headers, CLASSROOM_COOKIE)?;
+        touch(&self.participants, id).await.then_some(id)
+    }
+
+    async fn is_lecturer(&self, headers: &HeaderMap) -> bool {
+        match cookie_id(headers, LECTURER_COOKIE) {
+            Some(id) => touch(&self.lecturers, id).await,
+            None => false,
+        }
+    }
+
+    /// cutoff 이후로 사용하지 않은 참가자 id와 강사 id를 지움
+    pub async fn remove_expired(&self, cutoff: Instant) {
+        self.participants.write().await.retain(|_, &mut last_used| last_used >= cutoff);
+        self.lecturers.write().await.retain(|_, &mut last_used| last_used >= cutoff);
+    }
+
+    /// 새로 연결했거나 메시지를 놓친 사람에게 지금 상태를 알려주는 메시지들
+    async fn snapshot(&self, role: Role) -> Vec<String> {
+        let stage = self.stage.read().await;
+        let round = match &*stage {
+            Stage::Waiting => return vec![encode(&ServerMessage::Waiting)],
+            Stage::Finished => return vec![encode(&ServerMessage::Finished)],
+            Stage::Question(round) => round,
+        };
+
+        let mut messages = vec![round.question_message()];
+        if let Role::Participant(id) = role
+            && let Some((submitted, _)) = round.answers.get(&id)
+        {
+            messages.push(encode(&ServerMessage::Answered { submitted }));
+        }
+        if !round.open {
+            messages.push(round.closed_message());
+        } else if role == Role::Lecturer {
+            messages.push(round.distribution_message());
+        }
+        messages
+    }
+
+    fn broadcast(&self, message: String, lecturer_only: bool) {
+        // 연결된 사람이 없으면 보내지 않아도 됨
+        let _ = self.events.send(ClassroomEvent {
+            lecturer_only,
+            message,
+        });
+    }
+
+    async fn handle_lecturer(&self, state: &ServiceState, command: LecturerCommand) -> Vec<String> {
+        let mut stage = self.stage.write().await;
+        match command {
+            LecturerCommand::Show { index } => {
+                let questions = state.question_set().await.questions;
+                let Some(question) = questions.get(index) else {
+                    return vec![encode(&ServerMessage::Rejected {
+                        reason: "no such question",
+                    })];
+                };
+                let round = Round {
+                    index,
+                    count: questions.len(),
+                    question,
+                    open: true,
+                    order: question.display_order(),
+                    answers: HashMap::new(),
+                };
+                self.broadcast(round.question_message(), false);
+                self.broadcast(round.distribution_message(), true);
+                *stage = Stage::Question(round);
+            }
+            LecturerCommand::Close => {
+                if let Stage::Question(round) = &mut *stage
+                    && round.open
+                {
+                    round.open = false;
+                    self.broadcast(round.closed_message(), false);
+                }
+            }
+            LecturerCommand::Finish => {
+                *stage = Stage::Finished;
+                self.broadcast(encode(&ServerMessage::Finished), false);
+            }
+        }
+        vec![]
+    }
+
+    async fn handle_participant(&self, id: u128, command: ParticipantCommand) -> Vec<String> {
+        let ParticipantCommand::Answer { submitted } = command;
+        let mut stage = self.stage.write().await;
+        let Stage::Question(round) = &mut *stage else {
+            return vec![encode(&ServerMessage::Rejected {
+                reason: "no open question",
+            })];
+        };
+        if !round.open {
+            return vec![encode(&ServerMessage::Rejected {
+                reason: "question is closed",
+            })];
+        }
+        let Ok(answers) = SubmittedAnswer::parse(round.question, &submitted) else {
+            return vec![encode(&ServerMessage::Rejected {
+                reason: "invalid answer",
+            })];
+        };
+
+        // 아무것도 고르지 않았다면 제출을 취소한 것으로 봄
+        if submitted.is_empty() {
+            round.answers.remove(&id);
+        } else {
+            let correct = round.question.test(&answers);
+            round.answers.insert(id, (submitted.clone(), correct));
+        }
+        self.broadcast(round.distribution_message(), true);
+        vec![encode(&ServerMessage::Answered { submitted: &submitted })]
+    }
+}
+
+/// 참가자 페이지. 다시 연결해도 같은 참가자로 인식할 수 있도록 처음 접속할 때 참가자 id를 쿠키로 발급함.
+pub async fn participant_page(state: &ServiceState, headers: &HeaderMap) -> Response {
+    let page = render_template(&ParticipantPage { title: &state.config.title });
+    if state.classroom.participant_id(headers).await.is_some() {
+        return page.into_response();
+    }
+
+    // 서버가 다시 시작되어 모르는 id를 가진 참가자에게도 새 id를 발급함
+    let id = random_u128();
+    state.classroom.participants.write().await.insert(id, Instant::now());
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(
+            header::SET_COOKIE,
+            format!("{}={:X}; Path=/classroom; HttpOnly; SameSite=Lax", CLASSROOM_COOKIE, id),
+        )
+        .body(Body::from(page.0))
+        .unwrap()
+}
+
+pub async fn participant_socket(state: Arc<ServiceState>, headers: &HeaderMap, upgrade: WebSocketUpgrade) -> Response {
+    let Some(id) = state.classroom.participant_id(headers).await else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    upgrade.on_upgrade(move |socket| async move { run_socket(&state, socket, Role::Participant(id)).await })
+}
+
+/// 강사 제어 페이지. 강사 키가 설정되어 있지 않으면 실시간 모드를 사용할 수 없음.
+/// 강사 쿠키가 없으면 강사 키를 입력하는 칸을 보여줌.
+pub async fn control_page(state: &ServiceState, headers: &HeaderMap) -> Response {
+    if state.config.classroom.lecturer_key.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let authorized = state.classroom.is_lecturer(headers).await;
+    render_template(&ControlPage { title: &state.config.title, authorized, rejected: false }).into_response()
+}
+
+/// 강사 키를 확인하고 강사 쿠키를 발급함
+pub async fn control_login(state: &ServiceState, request: ControlLogin) -> Response {
+    let Some(lecturer_key) = state.config.classroom.lecturer_key.as_deref() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !constant_time_eq(lecturer_key.as_bytes(), request.key.as_bytes()) {
+        let page = ControlPage { title: &state.config.title, authorized: false, rejected: true };
+        return (StatusCode::UNAUTHORIZED, render_template(&page)).into_response();
+    }
+
+    let id = random_u128();
+    state.classroom.lecturers.write().await.insert(id, Instant::now());
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, "/classroom/control")
+        .header(
+            header::SET_COOKIE,
+            format!("{}={:X}; Path=/classroom/control; HttpOnly; SameSite=Strict", LECTURER_COOKIE, id),
+        )
+        .body(Body::empty())
+        .unwrap()
+}
+
+pub async fn control_socket(state: Arc<ServiceState>, headers: &HeaderMap, upgrade: WebSocketUpgrade) -> Response {
+    if state.config.classroom.lecturer_key.is_none() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    if !state.classroom.is_lecturer(headers).await {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    upgrade.on_upgrade(move |socket| async move { run_socket(&state, socket, Role::Lecturer).await })
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ControlLogin {
+    #[serde(default)]
+    pub key: String,
+}
+
+/// 발급한 id이면 마지막으로 사용한 시간을 갱신함
+async fn touch(ids: &RwLock<HashMap<u128, Instant>>, id: u128) -> bool {
+    match ids.write().await.get_mut(&id) {
+        Some(last_used) => {
+            *last_used = Instant::now();
+            true
+        }
+        None => false,
+    }
+}
+
+/// 강사 키를 비교하는 데 걸리는 시간으로 키를 알아낼 수 없도록 항상 모든 바이트를 비교함
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// WebSocket 연결 하나를 처리함. 연결이 끊어질 때까지 반환하지 않음.
+pub async fn run_socket(state: &ServiceState, mut socket: WebSocket, role: Role) {
+    let classroom = &state.classroom;
+    // 지금 상태를 읽기 전에 구독해야 그 사이에 바뀐 내용을 놓치지 않음
+    let mut events = classroom.events.subscribe();
+    if send_all(&mut socket, classroom.snapshot(role).await).await.is_err() {
+        return;
+    }
+
+    loop {
+        let replies = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match role {
+                    Role::Lecturer => match serde_json::from_str(&text) {
+                        Ok(command) => classroom.handle_lecturer(state, command).await,
+                        Err(e) => {
+                            error!("Invalid lecturer command {}: {}", text.as_str(), e);
+                            continue;
+                        }
+                    },
+                    Role::Participant(id) => match serde_json::from_str(&text) {
+                        Ok(command) => classroom.handle_participant(id, command).await,
+                        Err(_) => continue,
+                    },
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+            event = events.recv() => match event {
+                Ok(event) if event.lecturer_only && role != Role::Lecturer => continue,
+                Ok(event) => vec![event.message],
+                // 너무 느린 연결은 놓친 메시지 대신 지금 상태를 다시 받음
+                Err(RecvError::Lagged(_)) => classroom.snapshot(role).await,
+                Err(RecvError::Closed) => return,
+            },
+        };
+
+        if send_all(&mut socket, replies).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn send_all(socket: &mut WebSocket, messages: Vec<String>) -> Result<(), axum::Error> {
+    for message in messages {
+        socket.send(Message::Text(message.into())).await?;
+    }
+    Ok(())
+}
+
+fn encode(message: &ServerMessage) -> String {
+    serde_json::to_string(message).unwrap()
+}
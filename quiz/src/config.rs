@@ -0,0 +1,241 @@
+//! 퀴즈 서버 설정. TOML 설정 파일을 읽은 뒤 환경 변수로 덮어씀.
+//!
+//! | 설정 파일                | 환경 변수               |
+//! |--------------------------|-------------------------|
+//! | `bind_address`           | `QUIZ_BIND_ADDRESS`     |
+//! | `title`                  | `QUIZ_TITLE`            |
+//! | `submission_store`       | `QUIZ_SUBMISSION_STORE` |
+//! | `submission_dir`         | `QUIZ_SUBMISSION_DIR`   |
+//! | `submission_db`          | `QUIZ_SUBMISSION_DB`    |
+//! | `questions_dir`          | `QUESTIONS_DIR`         |
+//! | `discord.client_id`      | `DISCORD_CLIENT_ID`     |
+//! | `discord.secret`         | `DISCORD_SECRET`        |
+//! | `discord.redirect_uri`   | `DISCORD_REDIRECT`      |
+//! | `discord.guild_id`       | `DISCORD_GUILD_ID`      |
+//! | `classroom.lecturer_key` | `QUIZ_LECTURER_KEY`     |
+//! | `admin.ids`              | `QUIZ_ADMIN_IDS`        |
+//! | `admin.study_file`       | `QUIZ_STUDY_FILE`       |
+
+use serde::Deserialize;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::error;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub bind_address: SocketAddr,
+    /// 페이지 제목과 링크 미리보기에 표시되는 퀴즈 이름
+    pub title: String,
+    /// 제출 기록을 저장하는 방법
+    pub submission_store: SubmissionBackend,
+    /// filesystem 저장소가 제출 기록을 저장하는 폴더
+    pub submission_dir: PathBuf,
+    /// sqlite 저장소의 데이터베이스 파일
+    pub submission_db: PathBuf,
+    /// 지정하면 빌드 시간에 포함된 문제 대신 이 폴더의 문제를 읽고, 바뀔 때마다 다시 읽음
+    pub questions_dir: Option<PathBuf>,
+    pub discord: DiscordConfig,
+    pub time_limits: TimeLimits,
+    pub classroom: ClassroomConfig,
+    pub admin: AdminConfig,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionBackend {
+    /// `{submission_dir}/{디스코드 id}/{제출 시간}.json`
+    #[default]
+    Filesystem,
+    /// `submission_db` 파일 하나
+    Sqlite,
+}
+
+/// 디스코드 OAuth 설정. 하나라도 비어 있으면 온라인 제출이 비활성화됨.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    pub client_id: Option<String>,
+    pub secret: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub guild_id: Option<String>,
+}
+
+/// 강사가 진행하는 실시간 퀴즈 설정
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClassroomConfig {
+    /// 제어 페이지에 접속할 때 필요한 키. 없으면 실시간 퀴즈를 진행할 수 없음.
+    pub lecturer_key: Option<String>,
+}
+
+/// 관리자 페이지 설정. 디스코드 로그인을 사용하므로 디스코드 설정도 필요함.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// 관리자 페이지에 접속할 수 있는 디스코드 사용자 id들
+    pub ids: Vec<String>,
+    /// 지정하면 스터디 정보 파일(`study.toml`)의 `lecturer.id`도 관리자로 추가함
+    pub study_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeLimits {
+    /// 퀴즈 세션이 유지되는 시간
+    pub session_minutes: u64,
+    /// 디스코드 로그인을 마쳐야 하는 시간
+    pub oauth_minutes: u64,
+    /// 지정하면 세션을 시작한 뒤 이 시간이 지나면 답안을 바꿀 수 없음
+    pub quiz_minutes: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_address: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 8080)),
+            title: "코딩랩 Rust 스터디 중간평가".to_string(),
+            submission_store: SubmissionBackend::default(),
+            submission_dir: PathBuf::from("submissions"),
+            submission_db: PathBuf::from("submissions.db"),
+            questions_dir: None,
+            discord: DiscordConfig::default(),
+            time_limits: TimeLimits::default(),
+            classroom: ClassroomConfig::default(),
+            admin: AdminConfig::default(),
+        }
+    }
+}
+
+impl Default for TimeLimits {
+    fn default() -> Self {
+        TimeLimits {
+            session_minutes: 180,
+            oauth_minutes: 5,
+            quiz_minutes: None,
+        }
+    }
+}
+
+impl Config {
+    /// 설정 파일을 읽고 환경 변수를 적용함. 파일이 없으면 기본값을 사용함.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let mut config = match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str::<Config>(&contents)
+                .map_err(|e| format!("Invalid config file {}:\n{}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(format!("Unable to read config file {}: {}", path.display(), e)),
+        };
+        config.apply_env()?;
+        if let Some(path) = config.admin.study_file.clone() {
+            config.admin.ids.push(lecturer_id(&path)?);
+        }
+        Ok(config)
+    }
+
+    fn apply_env(&mut self) -> Result<(), String> {
+        if let Some(address) = env("QUIZ_BIND_ADDRESS") {
+            self.bind_address = SocketAddr::from_str(&address)
+                .map_err(|e| format!("Invalid QUIZ_BIND_ADDRESS {}: {}", address, e))?;
+        }
+        if let Some(title) = env("QUIZ_TITLE") {
+            self.title = title;
+        }
+        if let Some(dir) = env("QUIZ_SUBMISSION_DIR") {
+            self.submission_dir = PathBuf::from(dir);
+        }
+        if let Some(backend) = env("QUIZ_SUBMISSION_STORE") {
+            self.submission_store = match backend.as_str() {
+                "filesystem" => SubmissionBackend::Filesystem,
+                "sqlite" => SubmissionBackend::Sqlite,
+                _ => return Err(format!("Invalid QUIZ_SUBMISSION_STORE {}: expected filesystem or sqlite", backend)),
+            };
+        }
+        if let Some(path) = env("QUIZ_SUBMISSION_DB") {
+            self.submission_db = PathBuf::from(path);
+        }
+        if let Some(dir) = env("QUESTIONS_DIR") {
+            self.questions_dir = Some(PathBuf::from(dir));
+        }
+
+        if let Some(key) = env("QUIZ_LECTURER_KEY") {
+            self.classroom.lecturer_key = Some(key);
+        }
+        if let Some(ids) = env("QUIZ_ADMIN_IDS") {
+            self.admin.ids = ids.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+        }
+        if let Some(path) = env("QUIZ_STUDY_FILE") {
+            self.admin.study_file = Some(PathBuf::from(path));
+        }
+
+        let discord = &mut self.discord;
+        discord.client_id = env("DISCORD_CLIENT_ID").or(discord.client_id.take());
+        discord.secret = env("DISCORD_SECRET").or(discord.secret.take());
+        discord.redirect_uri = env("DISCORD_REDIRECT").or(discord.redirect_uri.take());
+        discord.guild_id = env("DISCORD_GUILD_ID").or(discord.guild_id.take());
+        Ok(())
+    }
+}
+
+impl DiscordConfig {
+    /// 모든 항목이 설정되어 있으면 (client_id, secret, redirect_uri, guild_id)를 반환함.
+    /// 빈 문자열은 설정되지 않은 것으로 취급함.
+    pub fn complete(&self) -> Option<(&str, &str, &str, &str)> {
+        let fields = [
+            ("client_id", &self.client_id),
+            ("secret", &self.secret),
+            ("redirect_uri", &self.redirect_uri),
+            ("guild_id", &self.guild_id),
+        ];
+        for (name, value) in fields {
+            if value.as_deref().is_none_or(str::is_empty) {
+                error!("Discord setting {} is not configured", name);
+            }
+        }
+
+        Some((
+            non_empty(&self.client_id)?,
+            non_empty(&self.secret)?,
+            non_empty(&self.redirect_uri)?,
+            non_empty(&self.guild_id)?,
+        ))
+    }
+}
+
+impl TimeLimits {
+    pub fn session_lifetime(&self) -> Duration {
+        Duration::from_mins(self.session_minutes)
+    }
+
+    pub fn oauth_timeout(&self) -> Duration {
+        Duration::from_mins(self.oauth_minutes)
+    }
+
+    pub fn quiz_duration(&self) -> Option<Duration> {
+        self.quiz_minutes.map(Duration::from_mins)
+    }
+}
+
+/// 스터디 정보 파일에서 강사의 디스코드 id를 읽음
+fn lecturer_id(path: &Path) -> Result<String, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read study file {}: {}", path.display(), e))?;
+    let study = toml::from_str::<toml::Table>(&contents)
+        .map_err(|e| format!("Invalid study file {}:\n{}", path.display(), e))?;
+    study
+        .get("lecturer")
+        .and_then(|v| v.get("id"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| format!("Study file {} has no lecturer.id", path.display()))
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().filter(|v| !v.is_empty())
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
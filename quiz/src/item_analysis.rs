@@ -0,0 +1,226 @@
+//! 문항 분석. 제출 기록으로 문제마다 정답률, 변별도, 선택지별 선택 비율을 계산해
+//! 너무 쉽거나 어려운 문제, 오답을 유도하는 선택지를 찾을 수 있게 함.
+//! 서술형 선택지가 있는 문제는 작성한 답을 정규화해 묶어서 자주 나온 오답을 보여줌.
+
+use crate::submission_store::Submission;
+use crate::{Answer, Question, find_question};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 변별도를 계산할 때 사용하는 상위, 하위 집단의 비율
+const GROUP_RATIO: f32 = 0.27;
+
+/// 제출 하나에 들어 있는 문제 하나의 답안
+struct Response<'a> {
+    /// 제출 기록의 번호
+    submission: usize,
+    correct: bool,
+    answer: &'a [String],
+}
+
+/// 문제 하나의 분석 결과
+#[derive(Serialize, Debug, Clone)]
+pub struct ItemReport {
+    pub question: String,
+    /// 지금 문제 목록에 없는 문제이면 None
+    pub file: Option<&'static str>,
+    pub name: Option<&'static str>,
+    /// 이 문제를 푼 사람 수
+    pub responses: usize,
+    /// 정답률 (0 ~ 100)
+    pub difficulty: f32,
+    /// 총점 상위 27% 집단과 하위 27% 집단의 정답률 차이 (-1 ~ 1). 응답이 두 개보다 적으면 None.
+    pub discrimination: Option<f32>,
+    /// 객관식 선택지별 선택 횟수. 서술형 선택지는 포함하지 않음.
+    pub choices: Vec<ChoiceFrequency>,
+    /// 서술형, 숫자 선택지에 작성한 답을 정규화해 묶은 것. 많이 나온 순서로 정렬함.
+    /// 서술형, 숫자 선택지가 없는 문제이면 비어 있음.
+    pub subjective: Vec<AnswerCluster>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ChoiceFrequency {
+    pub index: usize,
+    pub label: &'static str,
+    /// 정답 선택지인지 여부
+    pub answer: bool,
+    pub count: usize,
+    /// 선택 비율 (0 ~ 100)
+    pub rate: f32,
+}
+
+/// 정규화했을 때 같은 서술형 답들
+#[derive(Serialize, Debug, Clone)]
+pub struct AnswerCluster {
+    /// 앞뒤 공백을 지우고, 연속된 공백을 하나로 줄이고, 소문자로 바꾼 답
+    pub normalized: String,
+    pub count: usize,
+    /// 작성한 형태 중 하나라도 정답으로 채점되었는지 여부
+    pub accepted: bool,
+    /// 실제로 작성한 형태들. 많이 나온 순서로 정렬함.
+    pub variants: Vec<AnswerVariant>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AnswerVariant {
+    pub text: String,
+    pub count: usize,
+}
+
+/// 제출 기록들을 분석함. 한 사람이 여러 번 제출했다면 멤버별 최신 기록만 넘겨야 같은 사람이 여러 번 세어지지 않음.
+/// 결과는 문제 목록의 순서를 따르고, 목록에 없는 문제는 id 순서로 뒤에 붙음.
+pub fn analyze(submissions: &[Submission], questions: &'static [Question]) -> Vec<ItemReport> {
+    let mut responses: HashMap<&str, Vec<Response>> = HashMap::new();
+    // 예전 기록은 문제별 정답 여부를 알 수 없으므로 응답에 포함하지 않음
+    for (i, submission) in submissions.iter().enumerate().filter(|(_, v)| !v.result.legacy) {
+        for answer in &submission.result.answers {
+            responses.entry(answer.question.as_str()).or_default().push(Response {
+                submission: i,
+                correct: answer.correct,
+                answer: &answer.answer,
+            });
+        }
+    }
+
+    let (top, bottom) = score_groups(submissions);
+
+    let mut ids: Vec<&str> = questions.iter().map(|v| v.id).collect();
+    let mut unknown: Vec<&str> = responses
+        .keys()
+        .copied()
+        .filter(|id| find_question(questions, id).is_none())
+        .collect();
+    unknown.sort();
+    ids.extend(unknown);
+
+    ids.into_iter()
+        .map(|id| {
+            let question = find_question(questions, id).map(|v| &questions[v]);
+            let responses = responses.get(id).map(Vec::as_slice).unwrap_or_default();
+            let correct = responses.iter().filter(|v| v.correct).count();
+
+            let correct_rate = |group: &[usize]| {
+                let answered: Vec<bool> = responses
+                    .iter()
+                    .filter(|v| group.contains(&v.submission))
+                    .map(|v| v.correct)
+                    .collect();
+                (!answered.is_empty())
+                    .then(|| answered.iter().filter(|&&v| v).count() as f32 / answered.len() as f32)
+            };
+            let discrimination = match (correct_rate(&top), correct_rate(&bottom)) {
+                (Some(top), Some(bottom)) if responses.len() >= 2 => Some(top - bottom),
+                _ => None,
+            };
+
+            ItemReport {
+                question: id.to_string(),
+                file: question.map(|v| v.file),
+                name: question.map(|v| v.name),
+                responses: responses.len(),
+                difficulty: percent(correct, responses.len()),
+                discrimination,
+                choices: question.map(|v| choice_frequencies(v, responses)).unwrap_or_default(),
+                subjective: question.map(|v| answer_clusters(v, responses)).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// 총점 순서로 상위, 하위 집단에 속하는 제출 번호들을 구함
+fn score_groups(submissions: &[Submission]) -> (Vec<usize>, Vec<usize>) {
+    let mut ranked: Vec<usize> = (0..submissions.len()).collect();
+    ranked.sort_by(|&a, &b| submissions[b].result.score.total_cmp(&submissions[a].result.score));
+
+    let size = ((submissions.len() as f32 * GROUP_RATIO).round() as usize).max(1);
+    // 제출이 적으면 두 집단이 겹치지 않도록 절반까지만 사용함
+    let size = size.min(submissions.len() / 2);
+    let top = ranked[..size].to_vec();
+    let bottom = ranked[ranked.len() - size..].to_vec();
+    (top, bottom)
+}
+
+fn choice_frequencies(question: &Question, responses: &[Response]) -> Vec<ChoiceFrequency> {
+    question
+        .choices
+        .iter()
+        .enumerate()
+        .filter_map(|(index, choice)| {
+            let Answer::Choice { label } = choice else {
+                return None;
+            };
+            let count = responses
+                .iter()
+                .filter(|v| v.answer.iter().any(|v| same_label(v, label)))
+                .count();
+            Some(ChoiceFrequency {
+                index,
+                label,
+                answer: question.answer.contains(&index),
+                count,
+                rate: percent(count, responses.len()),
+            })
+        })
+        .collect()
+}
+
+/// 서술형 답을 정규화해 묶음. 제출 기록에는 어느 선택지에 작성한 답인지 남지 않으므로,
+/// 객관식 선택지 라벨과 다른 값은 모두 서술형 답으로 취급함.
+fn answer_clusters(question: &Question, responses: &[Response]) -> Vec<AnswerCluster> {
+    let has_subjective = question
+        .choices
+        .iter()
+        .any(|v| matches!(v, Answer::Subjective { .. } | Answer::Numeric { .. } | Answer::Output { .. }));
+    if !has_subjective {
+        return vec![];
+    }
+
+    // 정규화한 답 -> 작성한 형태 -> 횟수
+    let mut clusters: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+    for value in responses.iter().flat_map(|v| v.answer) {
+        let is_label = question.choices.iter().any(|choice| match choice {
+            Answer::Choice { label } | Answer::Step { label } => same_label(value, label),
+            Answer::Subjective { .. } | Answer::Numeric { .. } | Answer::Output { .. } => false,
+        });
+        let normalized = normalize(value);
+        if is_label || normalized.is_empty() {
+            continue;
+        }
+        *clusters.entry(normalized).or_default().entry(value.trim()).or_default() += 1;
+    }
+
+    let mut clusters: Vec<AnswerCluster> = clusters
+        .into_iter()
+        .map(|(normalized, variants)| {
+            let mut variants: Vec<AnswerVariant> = variants
+                .into_iter()
+                .map(|(text, count)| AnswerVariant { text: text.to_string(), count })
+                .collect();
+            variants.sort_by(|a, b| b.count.cmp(&a.count).then(a.text.cmp(&b.text)));
+            AnswerCluster {
+                accepted: variants.iter().any(|v| question.accepts_text(&v.text)),
+                count: variants.iter().map(|v| v.count).sum(),
+                normalized,
+                variants,
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.count.cmp(&a.count).then(a.normalized.cmp(&b.normalized)));
+    clusters
+}
+
+fn normalize(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// 채점할 때와 같은 규칙으로 선택지 라벨을 비교함
+fn same_label(value: &str, label: &str) -> bool {
+    value.trim().eq_ignore_ascii_case(label.trim())
+}
+
+fn percent(count: usize, total: usize) -> f32 {
+    match total {
+        0 => 0.0,
+        _ => count as f32 / total as f32 * 100.0,
+    }
+}
@@ -0,0 +1,112 @@
+//! 멤버별 점수 순위. 서버가 시작할 때 저장소의 모든 제출 기록으로 다시 만들고, 이후 제출할 때마다 갱신함.
+
+use crate::submission_store::{StoreError, Submission, SubmissionStore};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::spawn_blocking;
+
+/// 멤버 한 명의 점수
+#[derive(Serialize, Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub member_id: String,
+    /// 마지막으로 제출할 때의 닉네임
+    pub username: String,
+    pub latest_score: f32,
+    pub best_score: f32,
+    /// 제출한 횟수
+    pub submissions: usize,
+    pub last_submitted_at: DateTime<Local>,
+}
+
+/// 순위를 매길 때 사용하는 점수
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RankBy {
+    #[default]
+    Best,
+    Latest,
+}
+
+/// 순위 화면에 보내는 변경 사항
+#[derive(Debug, Clone)]
+pub enum LeaderboardUpdate {
+    /// 제출이 저장되어 멤버 한 명의 점수가 바뀜
+    Entry(LeaderboardEntry),
+    /// 관리자가 제출 기록을 지우거나 무효 처리해 순위표를 다시 만듦
+    Reset,
+}
+
+#[derive(Default, Debug)]
+pub struct Leaderboard {
+    entries: HashMap<String, LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// 저장소의 모든 제출 기록을 읽어 순위표를 다시 만듦
+    pub async fn load(store: Arc<dyn SubmissionStore>) -> Result<Self, StoreError> {
+        let submissions = spawn_blocking(move || store.submissions()).await.unwrap()?;
+        Ok(Self::from_submissions(&submissions))
+    }
+
+    /// 제출 기록들을 시간 순서로 반영해 순위표를 만듦. 무효 처리한 기록은 제외함.
+    pub fn from_submissions(submissions: &[Submission]) -> Self {
+        let mut leaderboard = Leaderboard::default();
+        for submission in submissions.iter().filter(|v| !v.invalidated) {
+            leaderboard.record(submission);
+        }
+        leaderboard
+    }
+
+    /// 제출 기록 하나를 반영하고 바뀐 멤버의 점수를 반환함
+    pub fn record(&mut self, submission: &Submission) -> LeaderboardEntry {
+        let score = submission.result.score;
+        let entry = self
+            .entries
+            .entry(submission.member_id.clone())
+            .or_insert_with(|| LeaderboardEntry {
+                member_id: submission.member_id.clone(),
+                username: submission.username.clone(),
+                latest_score: score,
+                best_score: score,
+                submissions: 0,
+                last_submitted_at: submission.submitted_at,
+            });
+
+        // 저장소에서 읽은 기록은 시간 순서지만, 혹시 모를 역순 입력에도 최신 기록이 남도록 함
+        if submission.submitted_at >= entry.last_submitted_at {
+            entry.username = submission.username.clone();
+            entry.latest_score = score;
+            entry.last_submitted_at = submission.submitted_at;
+        }
+        entry.best_score = entry.best_score.max(score);
+        entry.submissions += 1;
+        entry.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 점수가 높은 순서로 정렬함. 점수가 같으면 먼저 제출한 멤버가 앞에 옴.
+    pub fn ranking(&self, rank_by: RankBy) -> Vec<LeaderboardEntry> {
+        let score = |entry: &LeaderboardEntry| match rank_by {
+            RankBy::Best => entry.best_score,
+            RankBy::Latest => entry.latest_score,
+        };
+
+        let mut entries: Vec<LeaderboardEntry> = self.entries.values().cloned().collect();
+        entries.sort_by(|a, b| {
+            score(b)
+                .total_cmp(&score(a))
+                .then(a.last_submitted_at.cmp(&b.last_submitted_at))
+                .then(a.member_id.cmp(&b.member_id))
+        });
+        entries
+    }
+}
@@ -1,218 +1,212 @@
 #![warn(clippy::all)]
 
 use axum::body::Body;
-use axum::http::{HeaderValue, StatusCode, header};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use askama::Template;
+use axum::Json;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{Html, IntoResponse, Response};
 use chrono::Local;
+use question_format::markdown::{render_inline_markdown, render_markdown};
 use question_macro::include_questions;
 use rand::TryRngCore;
+use regex::Regex;
+use rand::seq::SliceRandom;
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
-use tokio::fs::{File, create_dir_all};
-use tokio::io::AsyncWriteExt;
-use tokio::sync::RwLock;
-use tokio::task::JoinSet;
+use tokio::sync::{RwLock, broadcast};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+use tokio::task::spawn_blocking;
 use tracing::error;
 use url::form_urlencoded::byte_serialize;
-
-
-pub const FIVE_MINUTES: Duration = Duration::from_mins(5);
-static QUIZ_TEMPLATE: &str = include_str!("../question_template.html");
-static FINISH_TEMPLATE: &str = include_str!("../finish_template.html");
+use crate::classroom::Classroom;
+use crate::config::Config;
+use crate::admin::AdminSession;
+use crate::leaderboard::{Leaderboard, LeaderboardUpdate, RankBy};
+use crate::submission_store::{Submission, SubmissionStore};
+
+pub mod admin;
+pub mod classroom;
+pub mod config;
+pub mod item_analysis;
+pub mod leaderboard;
+pub mod question_loader;
+pub mod submission_store;
+
+static SESSION_COOKIE: &str = "testSession";
 pub static QUESTIONS: &[Question] = include_questions!("questions");
 
 pub static ICON: &[u8] = include_bytes!("../favicon.png");
 pub static MIRACODE: &[u8] = include_bytes!("../Miracode.ttf");
+/// 코드 블록 색칠에 사용하는 색. question_format::highlight::theme_css()로 만든 파일임.
+pub static HIGHLIGHT_CSS: &[u8] = include_bytes!("../highlight.css");
 pub static PRETENDARD_VARIABLE: &[u8] = include_bytes!("../PretendardVariable.woff2");
 
-pub fn render_question(question: &Question, index: usize) -> Html<String> {
-    static CHOICE_OPTION_TEMPLATE: &str = r#"
-<input type="radio" id="optionCHOICE_INDEX" name="option" value="CHOICE_INDEX" style="cursor: pointer" onchange="updateAnswer()">
-<label for="optionCHOICE_INDEX" style="cursor: pointer">CHOICE_LABEL</label>
-<br/>
-"#;
-
-    static TEXT_OPTION_TEMPLATE: &str = r#"
-<input type="radio" id="optionCHOICE_INDEX" name="option" value="CHOICE_INDEX" style="cursor: pointer" onchange="updateAnswer()">
-<label for="optionCHOICE_INDEX" style="cursor: pointer">CHOICE_LABEL:</label>
-<input type="text" id="optionCHOICE_INDEXtext" style="font-family: 'Pretendard Variable',serif; font-weight: 200; background-color: #121212; border-radius: 4px; border-color: transparent; color: white; width: 128px" onchange="updateAnswer()">
-<br/>
-"#;
-
-    static CODE_TEMPLATE: &str = r#"
-<pre><code
-    id="code"
-    class="rust"
-    style="background-color: #121212;
-    border-radius: 15px;
-    text-align: left;
-    margin: 10px;
-    padding-left: 40px;
-    padding-top: 24px;
-    font-family: Miracode,monospace"
->QUESTION_CODE_CONTENTS</code></pre>
-"#;
-
-    let mut choices = String::new();
-    for (i, choice) in question.choices.iter().enumerate() {
-        let (template, label) = match choice {
-            Answer::Choice { label } => (CHOICE_OPTION_TEMPLATE, label),
-            Answer::Subjective { label, .. } => (TEXT_OPTION_TEMPLATE, label),
-        };
+/// 문제 페이지. 값은 askama가 escape 해서 넣고, 미리 HTML로 바꾼 마크다운과 코드만 `safe`로 그대로 넣음.
+#[derive(Template)]
+#[template(path = "question_template.html")]
+struct QuestionPage<'a> {
+    title: &'a str,
+    question: &'a Question,
+    description_html: String,
+    choices: Vec<ChoiceOption>,
+    /// 정답이 여러 개인 문제는 모두 고를 수 있도록 체크박스를 사용함
+    input_type: &'static str,
+    count: usize,
+}
 
-        choices.push_str(
-            &template
-                .replace("CHOICE_INDEX", format!("{}", i).as_str())
-                .replace("CHOICE_LABEL", label),
-        );
-    }
-    let answer = match question.choices[question.answer] {
-        Answer::Choice { .. } => question.answer.to_string(),
-        Answer::Subjective { value, .. } => format!("{} {}", question.answer, value),
+struct ChoiceOption {
+    index: usize,
+    kind: ChoiceKind,
+    label_html: String,
+    unit: &'static str,
+}
+
+enum ChoiceKind {
+    Choice,
+    Subjective,
+    Numeric,
+    /// 순서 문제는 항목마다 몇 번째인지 고르는 목록을 표시함
+    Step,
+    Output,
+}
+
+pub fn render_question(question: &Question, count: usize, title: &str) -> Html<String> {
+    let choices = question
+        .display_order()
+        .into_iter()
+        .map(|i| {
+            let (kind, label, unit) = match &question.choices[i] {
+                Answer::Choice { label } => (ChoiceKind::Choice, label, ""),
+                Answer::Step { label } => (ChoiceKind::Step, label, ""),
+                Answer::Output { label, .. } => (ChoiceKind::Output, label, ""),
+                Answer::Subjective { label, .. } => (ChoiceKind::Subjective, label, ""),
+                Answer::Numeric { label, unit, .. } => (ChoiceKind::Numeric, label, *unit),
+            };
+            ChoiceOption { index: i, kind, label_html: render_inline_markdown(label), unit }
+        })
+        .collect();
+
+    render_template(&QuestionPage {
+        title,
+        question,
+        description_html: render_markdown(question.description),
+        choices,
+        input_type: if question.answer.len() > 1 { "checkbox" } else { "radio" },
+        count,
+    })
+}
+
+pub async fn start_session(state: &ServiceState) -> Response {
+    let questions = state.question_set().await.questions;
+    let mut sequence: Vec<usize> = (0..questions.len()).collect();
+    sequence.shuffle(&mut rand::rng());
+    let Some(first) = sequence.first().map(|&v| questions[v].id) else {
+        return StatusCode::NOT_FOUND.into_response();
     };
-    let description = question.description.replace('\n', "<br/>");
-    let code = if !question.code.is_empty() {
-        CODE_TEMPLATE.replace("QUESTION_CODE_CONTENTS", question.code)
-    } else {
-        String::new()
+
+    let id = random_u128();
+    let session = QuizSession {
+        questions,
+        submitted: vec![vec![]; sequence.len()],
+        orders: sequence.iter().map(|&v| questions[v].display_order()).collect(),
+        sequence,
+        started: Instant::now(),
+        finished: false,
     };
+    // 세션 번호가 겹칠 일은 사실상 없으므로 덮어쓰기를 신경쓰지 않음
+    state.sessions.write().await.insert(id, session);
 
-    Html::from(
-        QUIZ_TEMPLATE
-            .replace("QUESTION_NAME", question.name)
-            .replace("QUESTION_DESCRIPTION", &description)
-            .replace("QUESTION_CODE", &code)
-            .replace("QUESTION_CHOICES", &choices)
-            .replace("QUESTION_NUMBER", &format!("{}", index + 1))
-            .replace("QUESTION_COUNT", &format!("{}", QUESTIONS.len()))
-            .replace("QUESTION_ANSWER", &answer),
-    )
-}
-
-pub fn render_redirect() -> Html<String> {
-    static REDIRECT_TEMPLATE: &str = r#"
-<!DOCTYPE html>
-<html lang="kr" xmlns="http://www.w3.org/1999/html">
-<head>
-    <meta charset="utf-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1, shrink-to-fit=no">
-
-    <title>코딩랩 Rust 스터디 중간평가</title>
-
-    <meta property="og:site_name" content="코딩랩 Rust 스터디 중간평가"/>
-    <meta property="og:title" content="코딩랩 Rust 스터디 중간평가"/>
-    <link rel="icon" href="./favicon.png"/>
-</head>
-<body>
-<script>
-/* Randomize array in-place using Durstenfeld shuffle algorithm */
-function shuffleArray(array) {
-    for (var i = array.length - 1; i > 0; i--) {
-        var j = Math.floor(Math.random() * (i + 1));
-        var temp = array[i];
-        array[i] = array[j];
-        array[j] = temp;
-    }
+    Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, format!("/q/{}", first))
+        .header(
+            header::SET_COOKIE,
+            format!("{}={:X}; Path=/; HttpOnly; SameSite=Lax", SESSION_COOKIE, id),
+        )
+        .body(Body::empty())
+        .unwrap()
 }
 
-let newSequence = Array(RANGE_SIZE).fill().map((element, index) => index)
-shuffleArray(newSequence)
-const defaultSubmitted = Array(RANGE_SIZE).fill("")
-const defaultCorrect = Array(RANGE_SIZE).fill(false)
-document.cookie = "testSequence=" + encodeURIComponent(JSON.stringify(newSequence))
-document.cookie = "testSubmitted=" + encodeURIComponent(JSON.stringify(defaultSubmitted))
-document.cookie = "testCorrect=" + encodeURIComponent(JSON.stringify(defaultCorrect))
-document.location.href = "./" + newSequence[0]
-</script>
-</body>
-</html>
-"#;
-    let max = QUESTIONS.len();
-    let contents = REDIRECT_TEMPLATE.replace("RANGE_SIZE", max.to_string().as_str());
-    Html::from(contents)
-}
-
-pub fn render_finish_page(oauth_provider: Option<Arc<DiscordData>>) -> Html<String> {
-    static DISCORD_UNAVAILABLE: &str = r#"
-        <p id="no_discord" style="display: none">:p</p>
-        <p style="color: yellow; font-family: 'Miracode',serif; font-weight: 600; font-size: 48px">☹</p>
-        <p style="color: white; font-family: 'Pretendard Variable',serif; font-weight: 600; font-size: 48px">디스코드 API가 비활성화 되어 있습니다! 온라인 제출이 불가능합니다!</p>
-        <p style="color: white; font-family: 'Pretendard Variable',serif; font-weight: 600; font-size: 24px">퀴즈 결과:</p>
-"#;
-
-    static OFFLINE_SCRIPT: &str = r#"
-    {
-        let cookieObject = getCookie()
-        if (cookieObject == null) {
-            document.location.href = "./"
-        }
+/// 마지막 페이지. 디스코드 API가 설정되어 있을 때만 온라인 제출 버튼을 보여줌.
+#[derive(Template)]
+#[template(path = "finish_template.html")]
+struct FinishPage<'a> {
+    title: &'a str,
+    count: usize,
+    online: bool,
+}
 
-        const resultList = document.getElementById("testResult")
-        for (let i = 0; i < QUESTION_COUNT; i++) {
-            const number = cookieObject.sequence[i]
-            const result = cookieObject.correct[i]
-
-            const text = document.createElement("a")
-            text.textContent = number
-            text.style.fontFamily = "Miracode"
-            text.style.display = "inline-block"
-            text.style.padding = "10px"
-            text.href = "./" + number
-            if (result) {
-                text.style.color = "green"
-            } else {
-                text.style.color = "red"
-            }
-            resultList.appendChild(text)
-        }
-    }
-"#;
+pub fn render_finish_page(
+    oauth_provider: Option<Arc<DiscordData>>,
+    count: usize,
+    title: &str,
+) -> Html<String> {
+    render_template(&FinishPage {
+        title,
+        count,
+        online: oauth_provider.is_some(),
+    })
+}
 
-    static DISCORD_AVAILABLE: &str = r#"
-    <p style="color: royalblue; font-family: 'Pretendard Variable',serif; font-weight: 300; font-size: 32px; cursor: pointer", onclick="submit()">제출하기</p>
-"#;
-    static ONLINE_SCRIPT: &str = r#"
-    {
-        let cookieObject = getCookie()
-        if (cookieObject == null) {
-            document.location.href = "./"
-        }
-    }
+#[derive(Template)]
+#[template(path = "leaderboard_template.html")]
+struct LeaderboardPage<'a> {
+    title: &'a str,
+}
 
-    function submit() {
-        const cookieObject = getCookie()
-        const jsonBody = JSON.stringify(cookieObject)
-        fetch("./submit", {
-            method: 'PUT',
-            headers: {
-                'Content-Type': 'application/json',
-            },
-            body: jsonBody
-        }).then(response => response.text())
-        .then(text => window.location.href = text)
-    }
-"#;
+pub fn render_leaderboard_page(title: &str) -> Html<String> {
+    render_template(&LeaderboardPage { title })
+}
 
-    let (submit, script) = match oauth_provider {
-        None => (DISCORD_UNAVAILABLE, OFFLINE_SCRIPT),
-        Some(_) => (DISCORD_AVAILABLE, ONLINE_SCRIPT),
-    };
+/// askama 템플릿을 HTML로 만듦. 템플릿은 컴파일할 때 검사하므로 String에 쓰는 동안에만 실패할 수 있음.
+pub fn render_template(template: &impl Template) -> Html<String> {
+    Html::from(template.render().expect("rendering a template into a String must not fail"))
+}
+
+pub async fn get_leaderboard(state: &ServiceState, query: LeaderboardQuery) -> Response {
+    let ranking = state.leaderboard.read().await.ranking(query.by);
+    Json(ranking).into_response()
+}
+
+/// 처음에는 전체 순위를 `snapshot` 이벤트로, 이후에는 제출이 저장될 때마다 바뀐 멤버의 점수를 `update` 이벤트로 보냄
+pub async fn leaderboard_events(state: Arc<ServiceState>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    // 전체 순위를 읽기 전에 구독해야 그 사이에 저장된 제출을 놓치지 않음
+    let receiver = state.leaderboard_updates.subscribe();
+    let snapshot = leaderboard_snapshot(&state).await;
+
+    let updates = BroadcastStream::new(receiver).then(move |update| {
+        let state = state.clone();
+        async move {
+            match update {
+                Ok(LeaderboardUpdate::Entry(entry)) => Event::default().event("update").json_data(entry).unwrap(),
+                Ok(LeaderboardUpdate::Reset) => leaderboard_snapshot(&state).await,
+                // 너무 느린 클라이언트는 놓친 변경 사항 대신 전체 순위를 다시 받음
+                Err(BroadcastStreamRecvError::Lagged(_)) => leaderboard_snapshot(&state).await,
+            }
+        }
+    });
+
+    let events = tokio_stream::once(snapshot).chain(updates).map(Ok);
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
 
-    let contents = FINISH_TEMPLATE
-        .replace("LARGE_MESSAGE", "수고하셨습니다!")
-        .replace("INIT_SCRIPT", script)
-        .replace("QUESTION_COUNT", QUESTIONS.len().to_string().as_str())
-        .replace("SUBMIT", submit);
-    Html::from(contents)
+async fn leaderboard_snapshot(state: &ServiceState) -> Event {
+    let ranking = state.leaderboard.read().await.ranking(RankBy::Best);
+    Event::default().event("snapshot").json_data(ranking).unwrap()
 }
 
 pub async fn serve_file(file_name: &str) -> Response {
     let (content_type, bytes) = match file_name {
         "favicon.png" => ("image/png", ICON),
         "Miracode.ttf" => ("font/ttf", MIRACODE),
+        "highlight.css" => ("text/css", HIGHLIGHT_CSS),
         "PretendardVariable.woff2" => ("font/woff2", PRETENDARD_VARIABLE),
         _ => return StatusCode::NOT_FOUND.into_response(),
     };
@@ -226,259 +220,250 @@ pub async fn serve_file(file_name: &str) -> Response {
         .unwrap()
 }
 
-pub async fn try_init_discord() -> Option<Arc<DiscordData>> {
-    let discord_client_id = std::env::var("DISCORD_CLIENT_ID")
-        .map_err(|e| {
-            error!(
-                "Unable to fetch environment variable DISCORD_CLIENT_ID: {:?}",
-                e
-            );
-        })
-        .ok()?;
-    let discord_secret = std::env::var("DISCORD_SECRET")
-        .map_err(|e| {
-            error!(
-                "Unable to fetch environment variable DISCORD_SECRET: {:?}",
-                e
-            );
-        })
-        .ok()?;
-    let discord_redirect = std::env::var("DISCORD_REDIRECT")
-        .map_err(|e| {
-            error!(
-                "Unable to fetch environment variable DISCORD_REDIRECT: {:?}",
-                e
-            );
-        })
-        .ok()?;
-    let discord_redirect_encoded: String = byte_serialize(discord_redirect.as_bytes()).collect();
-    let discord_guild_id = std::env::var("DISCORD_GUILD_ID")
-        .map_err(|e| {
-            error!(
-                "Unable to fetch environment variable DISCORD_GUILD_ID: {:?}",
-                e
-            );
-        })
-        .ok()?;
+pub async fn try_init_discord(config: &Config) -> Option<Arc<DiscordData>> {
+    let (client_id, secret, redirect_uri, guild_id) = config.discord.complete()?;
+    let redirect_uri_encoded: String = byte_serialize(redirect_uri.as_bytes()).collect();
 
     let discord_data = DiscordData {
-        client_id: discord_client_id,
-        secret: discord_secret,
-        redirect_uri: discord_redirect,
-        redirect_uri_encoded: discord_redirect_encoded,
-        guild_id: discord_guild_id,
+        client_id: client_id.to_string(),
+        secret: secret.to_string(),
+        redirect_uri: redirect_uri.to_string(),
+        redirect_uri_encoded,
+        guild_id: guild_id.to_string(),
+        oauth_timeout: config.time_limits.oauth_timeout(),
         oauth_attempts: Default::default(),
-        leaderboard: Default::default(),
     };
     Some(Arc::new(discord_data))
 }
 
 pub async fn save_answer(
-    discord: Arc<DiscordData>,
+    state: &ServiceState,
     username: String,
     sender_id: String,
     quiz_result: QuizResult,
 ) -> Result<(), StatusCode> {
-    let json_string = serde_json::to_string_pretty(&quiz_result).unwrap();
-    let sender_id_clone = sender_id.clone();
-
-    let data_updater = async move || {
-        let data = discord.as_ref();
-        let mut writer = data.leaderboard.write().await;
-        writer.insert(sender_id, quiz_result);
-        Ok(())
+    let submission = Submission {
+        id: String::new(),
+        member_id: sender_id.clone(),
+        username,
+        submitted_at: Local::now(),
+        result: quiz_result,
+        invalidated: false,
     };
 
-    let file_writer = async move || {
-        let sender_id = sender_id_clone;
-        let now = Local::now();
-        let submission_name = now.format("%Y-%m-%dT%H-%M-%S.json").to_string();
+    // 저장소는 파일이나 데이터베이스를 직접 다루므로 별도의 스레드에서 실행함
+    let store = state.submission_store.clone();
+    let (mut submission, save_result) = spawn_blocking(move || {
+        let result = store.save(&submission);
+        (submission, result)
+    })
+    .await
+    .unwrap();
 
-        let dir_path = format!("submissions/{}", sender_id);
-        let username_path = format!("submissions/{}/{}", sender_id, username);
-        let submission_path = format!("submissions/{}/{}", sender_id, submission_name);
-        if let Err(e) = create_dir_all(dir_path.as_str()).await {
+    match save_result {
+        Ok(id) => submission.id = id,
+        Err(e) => {
             error!(
-                "Unable to create submission directory at {}!\n{:?}\nData is not saved: {}",
-                dir_path, e, json_string
+                "Unable to save submission of \"{}\" ({})!\n{}\nData is not saved: {}",
+                submission.username,
+                submission.member_id,
+                e,
+                serde_json::to_string(&submission.result).unwrap()
             );
+            // 저장되지 않은 제출은 순위에도 반영하지 않음
             return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        };
-        if let Err(e) = File::create(username_path.as_str()).await {
-            error!(
-                "Unable to create username file at {}!\n{:?}\nMarker for \"{}\" is not created!",
-                username_path, e, username
-            );
         }
+    }
 
-        let mut file = match File::create(submission_path.as_str()).await {
-            Ok(f) => f,
-            Err(e) => {
-                error!(
-                    "Unable to create submission file at {}!\n{:?}\nData is not saved: {}",
-                    submission_path, e, json_string
-                );
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
-            }
-        };
+    let entry = state.leaderboard.write().await.record(&submission);
+    // 순위 화면을 보고 있는 사람이 없으면 보내지 않아도 됨
+    let _ = state.leaderboard_updates.send(LeaderboardUpdate::Entry(entry));
+    Ok(())
+}
 
-        if let Err(e) = file.write_all(json_string.as_bytes()).await {
-            error!(
-                "Unable to write submission entry at {}!\n{:?}\nData is not saved: {}",
-                submission_path, e, json_string
-            );
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+pub async fn handle_submit(
+    discord: Arc<DiscordData>,
+    state: &ServiceState,
+    headers: &HeaderMap,
+) -> Response {
+    let quiz_result = {
+        let sessions = state.sessions.read().await;
+        let Some(session) = session_id(headers).and_then(|id| sessions.get(&id)) else {
+            return StatusCode::UNAUTHORIZED.into_response();
+        };
+        match session.grade() {
+            Ok(v) => v,
+            Err(e) => return e.into_response(),
         }
+    };
 
-        if let Err(e) = file.flush().await {
-            error!(
-                "Unable to save submission entry at {}!\n{:?}\nData is not saved: {}",
-                submission_path, e, json_string
-            );
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        };
+    discord.authorize_url(OauthPurpose::Submit(quiz_result)).await.into_response()
+}
 
-        Ok(())
+/// 채점이 끝난 세션에서 다른 답이 맞는지 확인함. 시험 도중에 정답을 찾는 데 쓸 수 없도록 끝난 세션에서만 사용할 수 있음.
+pub async fn check_answer(state: &ServiceState, headers: &HeaderMap, id: &str, request: CheckRequest) -> Response {
+    let sessions = state.sessions.read().await;
+    let Some(session) = session_id(headers).and_then(|id| sessions.get(&id)) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    if !session.finished {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(index) = find_question(session.questions, id).filter(|v| session.sequence.contains(v)) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let question = &session.questions[index];
+    let submitted = match SubmittedAnswer::parse(question, &request.submitted) {
+        Ok(v) => v,
+        Err(code) => return code.into_response(),
     };
 
-    let mut join_set = JoinSet::new();
-    join_set.spawn(data_updater());
-    join_set.spawn(file_writer());
-    let result1 = join_set.join_next().await.unwrap().unwrap();
-    let result2 = join_set.join_next().await.unwrap().unwrap();
+    Json(CheckResponse { correct: question.test(&submitted) }).into_response()
+}
 
-    let mut result = result1;
-    if result.is_ok() {
-        result = result2;
-    }
+pub async fn get_session(state: &ServiceState, headers: &HeaderMap) -> Response {
+    let sessions = state.sessions.read().await;
+    let Some(session) = session_id(headers).and_then(|id| sessions.get(&id)) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
 
-    result
+    // 채점 결과는 세션이 끝난 뒤 finish_session에서만 알려줌
+    Json(SessionResponse {
+        sequence: session.sequence.iter().map(|&v| session.questions[v].id).collect(),
+        submitted: session.submitted.clone(),
+        orders: session.orders.clone(),
+        finished: session.finished,
+    })
+    .into_response()
 }
 
-pub async fn handle_submit(discord: Arc<DiscordData>, cookie: UserCookie) -> Response {
-    let mut rng = OsRng;
-    let mut salt = [0u8; 16];
-    rng.try_fill_bytes(&mut salt).unwrap();
-    let salt = u128::from_le_bytes(salt);
-    let salt_string = format!("{:X}", salt);
-    let url = format!(
-        "https://discord.com/oauth2/authorize?client_id={}&response_type=code&redirect_uri={}&state={}&scope=identify+guilds.members.read&prompt=none",
-        discord.client_id,
-        discord.redirect_uri_encoded.as_str(),
-        salt_string
-    );
-    debug_assert_eq!(cookie.correct.len(), cookie.sequence.len());
-    debug_assert_eq!(cookie.correct.len(), cookie.submitted.len());
-
-    let score = (cookie.correct.into_iter().filter(|v| *v).count() as f32) / cookie.submitted.len() as f32;
-    let score = score * 100.0;
-    let answers: Result<Vec<_>, StatusCode> = cookie.sequence.into_iter().zip(cookie.submitted.into_iter()).map(|(sequence, entry)| {
-        let Some(question) = QUESTIONS.get(sequence) else {
-            error!("Invalid Quiz Number detected!: {}", sequence);
-            return Err(StatusCode::BAD_REQUEST);
-        };
-        let name = format!("{}.md", question.name);
-        let answer_value = match entry.find(' ') {
-            Some(position) => {
-                entry[position + 1..].to_string()
-            }
-            None => {
-                let index: usize = entry.parse().unwrap();
-                let Some(choice) = question.choices.get(index) else {
-                    error!("Invalid Answer Number {} detected for question {}", index, question.name);
-                    return Err(StatusCode::BAD_REQUEST);
-                };
-                match choice {
-                    Answer::Choice { label } => label.to_string(),
-                    Answer::Subjective { .. } => {
-                        error!("Invalid Answer Number {} detected for question {}", index, question.name);
-                        return Err(StatusCode::BAD_REQUEST)
-                    },
-                }
+/// 세션의 채점을 마치고 문제별 결과와 해설을 반환함
+pub async fn finish_session(state: &ServiceState, headers: &HeaderMap) -> Response {
+    let mut sessions = state.sessions.write().await;
+    let Some(session) = session_id(headers).and_then(|id| sessions.get_mut(&id)) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    session.finished = true;
+
+    let review: Vec<ReviewEntry> = session
+        .sequence
+        .iter()
+        .zip(session.submitted.iter())
+        .map(|(&index, entries)| {
+            let question = &session.questions[index];
+            ReviewEntry {
+                question: question.id,
+                name: question.name,
+                correct: SubmittedAnswer::parse(question, entries)
+                    .is_ok_and(|v| question.test(&v)),
+                explanation: render_markdown(question.explanation),
             }
-        };
-        Ok((name, answer_value))
-    }).collect();
-    let answers = match answers {
-        Ok(v) => v,
-        Err(e) => return e.into_response()
+        })
+        .collect();
+
+    Json(review).into_response()
+}
+
+pub async fn save_session_answer(
+    state: &ServiceState,
+    headers: &HeaderMap,
+    id: &str,
+    request: CheckRequest,
+) -> Response {
+    let mut sessions = state.sessions.write().await;
+    let Some(session) = session_id(headers).and_then(|id| sessions.get_mut(&id)) else {
+        return StatusCode::UNAUTHORIZED.into_response();
     };
-    let quiz_result = QuizResult {
-        answers,
-        score,
+    if session.finished {
+        return StatusCode::CONFLICT.into_response();
+    }
+    if let Some(limit) = state.config.time_limits.quiz_duration()
+        && session.started.elapsed() > limit
+    {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    // 세션은 시작할 때의 문제 목록으로 채점함
+    let Some(index) = find_question(session.questions, id) else {
+        return StatusCode::NOT_FOUND.into_response();
     };
+    // 형식만 검사하고, 답을 바꿔가며 정답을 찾을 수 없도록 정답 여부는 알려주지 않음
+    if let Err(code) = SubmittedAnswer::parse(&session.questions[index], &request.submitted) {
+        return code.into_response();
+    }
+    let Some(position) = session.sequence.iter().position(|&v| v == index) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    session.submitted[position] = request.submitted;
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// id로 문제의 번호를 찾음. 문제 목록은 항상 id 순서로 정렬되어 있음.
+pub fn find_question(questions: &[Question], id: &str) -> Option<usize> {
+    questions.binary_search_by_key(&id, |v| v.id).ok()
+}
+
+/// 쿠키에서 서버가 발급한 퀴즈 세션 번호를 찾음
+pub fn session_id(headers: &HeaderMap) -> Option<u128> {
+    cookie_id(headers, SESSION_COOKIE)
+}
 
-    let now = Instant::now();
-    discord.oauth_attempts.write().await.insert(salt, (now, quiz_result));
+/// 쿠키에 16진수로 저장한 id를 읽음
+pub fn cookie_id(headers: &HeaderMap, cookie: &str) -> Option<u128> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == cookie)
+        .and_then(|(_, value)| u128::from_str_radix(value, 16).ok())
+}
 
-    url.into_response()
+pub(crate) fn random_u128() -> u128 {
+    let mut rng = OsRng;
+    let mut bytes = [0u8; 16];
+    rng.try_fill_bytes(&mut bytes).unwrap();
+    u128::from_le_bytes(bytes)
 }
 
-pub async fn oauth_redirect(param: OauthRedirectUrlParams, discord: Arc<DiscordData>) -> Response {
+pub async fn oauth_redirect(
+    param: OauthRedirectUrlParams,
+    discord: Arc<DiscordData>,
+    state: &ServiceState,
+) -> Response {
     let Ok(salt) = u128::from_str_radix(param.state.as_str(), 16) else {
         error!("State is not u128!: {}", param.state.as_str());
         return StatusCode::BAD_REQUEST.into_response();
     };
     let mut attempts_writer = discord.oauth_attempts.write().await;
-    let quiz_result = match attempts_writer.remove(&salt) {
+    let purpose = match attempts_writer.remove(&salt) {
         None => return StatusCode::UNAUTHORIZED.into_response(),
-        Some((time, result)) => {
+        Some((time, purpose)) => {
             let now = Instant::now();
             let duration = now.duration_since(time);
 
-            if duration > FIVE_MINUTES {
+            if duration > discord.oauth_timeout {
                 return StatusCode::UNAUTHORIZED.into_response();
             }
-            result
+            purpose
         }
     };
     drop(attempts_writer);
 
-    let client = reqwest::Client::new();
-    let request = client
-        .post("https://discord.com/api/v10/oauth2/token")
-        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
-        .body(format!(
-            "grant_type=authorization_code&code={}&redirect_uri={}",
-            param.code,
-            discord.redirect_uri_encoded.as_str()
-        ))
-        .basic_auth(
-            discord.client_id.as_str(),
-            Some(discord.secret.as_str()),
-        )
-        .build()
-        .unwrap();
-    let response = match client.execute(request).await {
-        Ok(v) => v,
-        Err(e) => {
-            error!("Unable to communicate with discord server!:\n{:?}", e);
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        }
-    };
-    if response.status() != StatusCode::OK {
-        error!("Discord server returned code {:?}. Response Body: {}", response.status(), response.text().await.unwrap());
-        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-    }
-
-    let response: OauthResponse = match response.json::<OauthResponse>().await {
+    let guild_member = match discord.guild_member(param.code.as_str()).await {
         Ok(v) => v,
-        Err(e) => {
-            error!("Failed to decode discord server's response!:\n{:?}", e);
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        }
+        Err(code) => return code.into_response()
     };
-    debug_assert!(response.scope.contains("guilds.members.read"));
-    debug_assert_eq!(response.token_type, "Bearer");
 
-    let guild_member = get_current_user_guild_profile(response.access_token.as_str(), discord.guild_id.as_str(), &client).await;
-    let guild_member = match guild_member {
-        Ok(v) => v,
-        Err(code) => return code.into_response()
+    let quiz_result = match purpose {
+        OauthPurpose::Submit(v) => v,
+        OauthPurpose::AdminLogin => return admin::complete_login(state, guild_member).await,
     };
-    let name = guild_member.nick.or(guild_member.user.global_name).unwrap_or(guild_member.user.username);
+    let name = guild_member.name().to_string();
 
-    let save_result = save_answer(discord, name.clone(), guild_member.user.id, quiz_result).await;
+    let save_result = save_answer(state, name.clone(), guild_member.user.id, quiz_result).await;
     match save_result {
         Ok(_) => {
             Response::builder()
@@ -505,14 +490,28 @@ pub async fn get_current_user_guild_profile(
     Ok(response.json::<DiscordGuildMember>().await.unwrap())
 }
 
+#[derive(Serialize)]
 pub struct Question {
+    /// 파일 이름이나 front matter의 id에서 가져온 고유한 식별자. URL과 제출 기록에 사용함.
+    id: &'static str,
+    /// 문제 폴더 기준 파일 이름
+    file: &'static str,
     name: &'static str,
     description: &'static str,
     code: &'static str,
+    /// 빌드할 때 색칠한 코드 블록 HTML. 코드 블록이 없으면 빈 문자열임.
+    code_html: &'static str,
     choices: &'static [Answer],
-    answer: usize,
+    /// 정답 선택지 번호들. 두 개 이상이면 "모두 고르시오" 문제임.
+    /// 순서 문제는 모든 항목의 번호가 정답 순서대로 들어 있음.
+    answer: &'static [usize],
+    /// 채점 후에 보여주는 해설. 없으면 빈 문자열임.
+    explanation: &'static str,
+    /// 서술형 답을 비교하는 방법
+    matching: Matching,
 }
 
+#[derive(Serialize)]
 pub enum Answer {
     /// 객관식
     Choice { label: &'static str },
@@ -520,10 +519,44 @@ pub enum Answer {
     /// 주관식
     Subjective {
         label: &'static str,
-        value: &'static str,
+        /// 정답으로 인정하는 답들
+        values: &'static [&'static str],
+    },
+
+    /// 숫자 답. 정답과의 차이가 tolerance 이하이면 정답.
+    Numeric {
+        label: &'static str,
+        value: f64,
+        tolerance: f64,
+        /// 입력 칸 옆에 표시하는 단위. 없으면 빈 문자열임.
+        unit: &'static str,
+    },
+
+    /// 순서 문제의 항목. 제출할 때는 객관식처럼 라벨을 고른 순서대로 보냄.
+    Step { label: &'static str },
+
+    /// 코드 블록의 실행 결과를 적는 답. 제출할 때는 서술형 답으로 보냄.
+    Output {
+        label: &'static str,
+        /// 빌드할 때 코드 블록을 실행해 얻은 표준 출력
+        output: &'static str,
     },
 }
 
+/// 서술형 답을 정답과 비교하는 방법. 문제 파일 front matter의 `match:`로 정함.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Matching {
+    /// 앞뒤 공백을 무시하고 영문 대소문자를 구분하지 않음
+    Exact,
+    /// 모든 공백을 무시하고 영문 대소문자를 구분하지 않음
+    Whitespace,
+    /// 숫자로 읽어 차이가 tolerance 이하이면 정답
+    Numeric { tolerance: f64 },
+    /// 정답을 정규식으로 취급해 답 전체가 일치하면 정답
+    Regex,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SubmittedAnswer {
     /// 객관식
@@ -531,14 +564,36 @@ pub enum SubmittedAnswer {
 
     /// 주관식
     Subjective { label: String, value: String },
-}
 
-pub type SubmissionData = HashMap<String, QuizResult>;
+    /// 숫자 답
+    Numeric { label: String, value: String },
+}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct QuizResult {
-    answers: Vec<(String, String)>,
-    score: f32
+    pub answers: Vec<GradedAnswer>,
+    pub score: f32,
+    /// 서버에서 채점하기 전의 버전이 저장한 기록. 문제별 정답 여부를 알 수 없어 문항 분석에서 제외함.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy: bool,
+}
+
+/// 서버에서 채점한 문제 하나의 결과
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GradedAnswer {
+    pub question: String,
+    pub answer: Vec<String>,
+    pub correct: bool,
+    /// 부분 점수 (0.0 ~ 1.0)
+    pub credit: f32,
+}
+
+/// 브라우저가 보내는 선택지 하나. 서술형 선택지는 작성한 답을 함께 보냄.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SubmittedEntry {
+    choice: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
 }
 
 #[expect(dead_code)]
@@ -548,14 +603,37 @@ pub struct DiscordData {
     redirect_uri: String,
     redirect_uri_encoded: String,
     guild_id: String,
-    pub oauth_attempts: RwLock<HashMap<u128, (Instant, QuizResult)>>,
-    leaderboard: RwLock<SubmissionData>,
+    pub oauth_timeout: Duration,
+    pub oauth_attempts: RwLock<HashMap<u128, (Instant, OauthPurpose)>>,
+}
+
+/// 디스코드 로그인을 마친 뒤 할 일
+pub enum OauthPurpose {
+    /// 퀴즈 결과를 제출함
+    Submit(QuizResult),
+    /// 관리자 페이지에 로그인함
+    AdminLogin,
 }
 
 pub struct ServiceState {
-    pub pre_rendered_questions: Vec<Html<String>>,
-    pub pre_rendered_redirect: Html<String>,
+    pub config: Config,
+    pub question_set: RwLock<Arc<QuestionSet>>,
+    pub sessions: RwLock<HashMap<u128, QuizSession>>,
+    pub submission_store: Arc<dyn SubmissionStore>,
+    pub leaderboard: RwLock<Leaderboard>,
+    /// 제출이 저장될 때마다 바뀐 멤버의 점수를, 관리자가 제출 기록을 고치면 순위표를 다시 만들었다고 보냄
+    pub leaderboard_updates: broadcast::Sender<LeaderboardUpdate>,
+    /// 강사가 진행하는 실시간 퀴즈의 상태
+    pub classroom: Classroom,
+    /// 디스코드로 로그인한 관리자들의 세션
+    pub admin_sessions: RwLock<HashMap<u128, AdminSession>>,
     pub discord_data: Option<Arc<DiscordData>>,
+}
+
+/// 문제 목록과 미리 만들어둔 페이지들. 개발 모드에서는 문제 파일이 바뀔 때마다 통째로 교체됨.
+pub struct QuestionSet {
+    pub questions: &'static [Question],
+    pub pre_rendered_questions: Vec<Html<String>>,
     pub pre_rendered_finish_page: Html<String>,
 }
 
@@ -590,41 +668,406 @@ pub struct DiscordGuildMember {
     nick: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct UserCookie {
-    correct: Vec<bool>,
+#[derive(Deserialize, Debug)]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    by: RankBy,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CheckRequest {
+    submitted: Vec<SubmittedEntry>,
+}
+
+/// 서버가 발급한 퀴즈 세션. 문제 순서와 답안은 서버에만 저장됨.
+pub struct QuizSession {
+    /// 세션을 시작할 때의 문제 목록. 도중에 문제가 다시 로드되어도 이 목록으로 채점함.
+    questions: &'static [Question],
     sequence: Vec<usize>,
-    submitted: Vec<String>,
+    submitted: Vec<Vec<SubmittedEntry>>,
+    /// 문제마다 선택지를 표시할 순서. 순서 문제의 항목이 학생마다 다른 순서로 보이도록 세션을 시작할 때 섞음.
+    orders: Vec<Vec<usize>>,
+    pub started: Instant,
+    /// 마지막 페이지에 도달해 채점이 끝났는지 여부. 이후로는 답안을 바꿀 수 없음.
+    finished: bool,
 }
 
-impl Question {
-    pub fn test(&self, answer: &SubmittedAnswer) -> bool {
-        self.choices[self.answer].eq(answer)
+#[derive(Serialize, Debug)]
+pub struct CheckResponse {
+    correct: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SessionResponse {
+    sequence: Vec<&'static str>,
+    submitted: Vec<Vec<SubmittedEntry>>,
+    /// 문제마다 선택지를 표시할 순서. 페이지는 미리 렌더링되어 있으므로 브라우저에서 이 순서로 다시 배치함.
+    orders: Vec<Vec<usize>>,
+    finished: bool,
+}
+
+/// 채점이 끝난 뒤 보여주는 문제별 결과와 해설
+#[derive(Serialize, Debug)]
+pub struct ReviewEntry {
+    question: &'static str,
+    name: &'static str,
+    correct: bool,
+    /// HTML로 바꾼 해설
+    explanation: String,
+}
+
+impl QuestionSet {
+    pub fn new(
+        questions: &'static [Question],
+        discord_data: Option<Arc<DiscordData>>,
+        title: &str,
+    ) -> Self {
+        let pre_rendered_questions = questions
+            .iter()
+            .map(|v| render_question(v, questions.len(), title))
+            .collect();
+        let pre_rendered_finish_page = render_finish_page(discord_data, questions.len(), title);
+
+        QuestionSet {
+            questions,
+            pre_rendered_questions,
+            pre_rendered_finish_page,
+        }
     }
 }
 
-impl PartialEq<SubmittedAnswer> for Answer {
-    fn eq(&self, other: &SubmittedAnswer) -> bool {
-        match self {
-            Answer::Choice { label } => {
-                let SubmittedAnswer::Choice { label: label_other } = other else {
-                    return false;
-                };
+impl DiscordData {
+    /// 디스코드 로그인 주소를 만들고, 로그인을 마친 뒤 할 일을 기억해둠
+    pub async fn authorize_url(&self, purpose: OauthPurpose) -> String {
+        let salt = random_u128();
+        let salt_string = format!("{:X}", salt);
+        let url = format!(
+            "https://discord.com/oauth2/authorize?client_id={}&response_type=code&redirect_uri={}&state={}&scope=identify+guilds.members.read&prompt=none",
+            self.client_id,
+            self.redirect_uri_encoded.as_str(),
+            salt_string
+        );
+
+        let now = Instant::now();
+        self.oauth_attempts.write().await.insert(salt, (now, purpose));
+        url
+    }
+
+    /// 로그인 코드를 토큰으로 바꾸고 서버 멤버 정보를 가져옴
+    async fn guild_member(&self, code: &str) -> Result<DiscordGuildMember, StatusCode> {
+        let client = reqwest::Client::new();
+        let request = client
+            .post("https://discord.com/api/v10/oauth2/token")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(format!(
+                "grant_type=authorization_code&code={}&redirect_uri={}",
+                code,
+                self.redirect_uri_encoded.as_str()
+            ))
+            .basic_auth(
+                self.client_id.as_str(),
+                Some(self.secret.as_str()),
+            )
+            .build()
+            .unwrap();
+        let response = match client.execute(request).await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Unable to communicate with discord server!:\n{:?}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+        if response.status() != StatusCode::OK {
+            error!("Discord server returned code {:?}. Response Body: {}", response.status(), response.text().await.unwrap());
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+
+        let response: OauthResponse = match response.json::<OauthResponse>().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to decode discord server's response!:\n{:?}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+        debug_assert!(response.scope.contains("guilds.members.read"));
+        debug_assert_eq!(response.token_type, "Bearer");
+
+        get_current_user_guild_profile(response.access_token.as_str(), self.guild_id.as_str(), &client).await
+    }
+}
+
+impl DiscordGuildMember {
+    /// 서버 닉네임, 전역 이름, 사용자 이름 중 처음으로 있는 것
+    pub fn name(&self) -> &str {
+        self.nick
+            .as_deref()
+            .or(self.user.global_name.as_deref())
+            .unwrap_or(self.user.username.as_str())
+    }
+}
+
+impl ServiceState {
+    pub async fn question_set(&self) -> Arc<QuestionSet> {
+        self.question_set.read().await.clone()
+    }
+}
+
+impl QuizSession {
+    pub fn grade(&self) -> Result<QuizResult, StatusCode> {
+        let answers: Result<Vec<_>, StatusCode> = self
+            .sequence
+            .iter()
+            .zip(self.submitted.iter())
+            .map(|(&sequence, entries)| {
+                let question = &self.questions[sequence];
+                let submitted = SubmittedAnswer::parse(question, entries)?;
+                Ok(GradedAnswer {
+                    question: question.id.to_string(),
+                    answer: submitted.iter().map(|v| v.value().to_string()).collect(),
+                    correct: question.test(&submitted),
+                    credit: question.credit(&submitted),
+                })
+            })
+            .collect();
+        let answers = answers?;
+        let total_credit: f32 = answers.iter().map(|v| v.credit).sum();
+        let score = total_credit / answers.len() as f32 * 100.0;
+
+        Ok(QuizResult { answers, score, legacy: false })
+    }
+}
 
+impl Question {
+    /// 고른 정답 수와 오답 수를 셈
+    fn tally(&self, answers: &[SubmittedAnswer]) -> (usize, usize) {
+        let hits = answers
+            .iter()
+            .filter(|answer| self.answer.iter().any(|&i| self.accepts(&self.choices[i], answer)))
+            .count();
+        (hits, answers.len() - hits)
+    }
+
+    /// 제출한 답이 선택지와 같은지 확인함. 서술형 답은 문제의 비교 방법을 따름.
+    fn accepts(&self, choice: &Answer, answer: &SubmittedAnswer) -> bool {
+        match (choice, answer) {
+            (
+                Answer::Choice { label } | Answer::Step { label },
+                SubmittedAnswer::Choice { label: label_other },
+            ) => {
                 label.trim().eq_ignore_ascii_case(label_other.trim())
             }
-            Answer::Subjective { label, value } => {
-                let SubmittedAnswer::Subjective {
+            (
+                Answer::Subjective { label, values },
+                SubmittedAnswer::Subjective {
+                    label: label_other,
+                    value,
+                },
+            ) => label.trim().eq_ignore_ascii_case(label_other.trim()) && self.matching.accepts(values, value),
+            (
+                Answer::Numeric { label, value, tolerance, unit },
+                SubmittedAnswer::Numeric {
                     label: label_other,
                     value: value_other,
-                } = other
-                else {
-                    return false;
-                };
-
+                },
+            ) => {
                 label.trim().eq_ignore_ascii_case(label_other.trim())
-                    && value.trim().eq_ignore_ascii_case(value_other.trim())
+                    && numbers_match(*value, *tolerance, strip_unit(value_other, unit))
+            }
+            (
+                Answer::Output { label, output },
+                SubmittedAnswer::Subjective {
+                    label: label_other,
+                    value,
+                },
+            ) => label.trim().eq_ignore_ascii_case(label_other.trim()) && self.output_matches(output, value),
+            _ => false,
+        }
+    }
+
+    /// 작성한 답이 정답인 서술형, 숫자 선택지 중 하나와 같은지 확인함
+    pub(crate) fn accepts_text(&self, text: &str) -> bool {
+        self.answer.iter().any(|&i| match &self.choices[i] {
+            Answer::Choice { .. } | Answer::Step { .. } => false,
+            Answer::Subjective { values, .. } => self.matching.accepts(values, text),
+            Answer::Numeric { value, tolerance, unit, .. } => numbers_match(*value, *tolerance, strip_unit(text, unit)),
+            Answer::Output { output, .. } => self.output_matches(output, text),
+        })
+    }
+
+    /// 작성한 실행 결과를 비교함. 줄바꿈 형식, 줄 끝의 공백과 마지막 빈 줄은 무시하고 나머지는 문제의 비교 방법을 따름.
+    fn output_matches(&self, output: &str, text: &str) -> bool {
+        self.matching.accepts(&[&normalize_output(output)], &normalize_output(text))
+    }
+
+    /// 항목을 순서대로 나열하는 문제인지 여부
+    pub fn is_ordering(&self) -> bool {
+        matches!(self.choices.first(), Some(Answer::Step { .. }))
+    }
+
+    /// 선택지를 표시할 순서. 순서 문제는 정답 순서가 드러나지 않도록 섞음.
+    pub fn display_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.choices.len()).collect();
+        if self.is_ordering() {
+            // 항목이 두 개 이상이므로 언젠가는 정답 순서와 다르게 섞임
+            while order.is_sorted() {
+                order.shuffle(&mut rand::rng());
+            }
+        }
+        order
+    }
+
+    /// 정답을 빠짐없이, 오답 없이 골랐는지 확인함. 순서 문제는 모든 항목을 정답 순서대로 나열해야 정답임.
+    pub fn test(&self, answers: &[SubmittedAnswer]) -> bool {
+        if self.is_ordering() {
+            return self.ordering_credit(answers) == 1.0;
+        }
+        let (hits, misses) = self.tally(answers);
+        hits == self.answer.len() && misses == 0
+    }
+
+    /// 부분 점수를 계산함. 오답 하나를 고를 때마다 맞힌 정답 하나가 상쇄됨.
+    pub fn credit(&self, answers: &[SubmittedAnswer]) -> f32 {
+        if self.is_ordering() {
+            return self.ordering_credit(answers);
+        }
+        let (hits, misses) = self.tally(answers);
+        hits.saturating_sub(misses) as f32 / self.answer.len() as f32
+    }
+
+    /// 순서 문제의 부분 점수. 두 항목씩 짝지었을 때 순서가 뒤바뀐 쌍의 비율(Kendall 거리)만큼 감점함.
+    /// 모든 항목을 나열하지 않았으면 0점임.
+    fn ordering_credit(&self, answers: &[SubmittedAnswer]) -> f32 {
+        // 제출한 순서대로 각 항목이 정답에서 몇 번째인지 구함
+        let positions: Option<Vec<usize>> = answers
+            .iter()
+            .map(|answer| self.answer.iter().position(|&i| self.accepts(&self.choices[i], answer)))
+            .collect();
+        let Some(positions) = positions else {
+            return 0.0;
+        };
+        if positions.len() != self.answer.len() {
+            return 0.0;
+        }
+
+        let pairs = positions.len() * (positions.len() - 1) / 2;
+        let swapped = positions
+            .iter()
+            .enumerate()
+            .map(|(i, a)| positions[i + 1..].iter().filter(|&b| a > b).count())
+            .sum::<usize>();
+        match pairs {
+            0 => 1.0,
+            _ => 1.0 - swapped as f32 / pairs as f32,
+        }
+    }
+}
+
+impl SubmittedAnswer {
+    /// 브라우저가 보낸 선택지들을 해석함. 아무것도 고르지 않았다면 빈 Vec을 반환함.
+    pub fn parse(
+        question: &Question,
+        entries: &[SubmittedEntry],
+    ) -> Result<Vec<SubmittedAnswer>, StatusCode> {
+        let mut answers = Vec::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            if entries[..i].iter().any(|v| v.choice == entry.choice) {
+                error!("Duplicate Answer Number {} detected for question {}", entry.choice, question.name);
+                return Err(StatusCode::BAD_REQUEST);
             }
+
+            let answer = match (question.choices.get(entry.choice), entry.text.as_ref()) {
+                (Some(Answer::Choice { label } | Answer::Step { label }), None) => SubmittedAnswer::Choice {
+                    label: label.to_string(),
+                },
+                (Some(Answer::Subjective { label, .. } | Answer::Output { label, .. }), Some(text)) => SubmittedAnswer::Subjective {
+                    label: label.to_string(),
+                    value: text.to_string(),
+                },
+                (Some(Answer::Numeric { label, .. }), Some(text)) => SubmittedAnswer::Numeric {
+                    label: label.to_string(),
+                    value: text.to_string(),
+                },
+                _ => {
+                    error!("Invalid Answer Number {} detected for question {}", entry.choice, question.name);
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+            };
+            answers.push(answer);
+        }
+
+        Ok(answers)
+    }
+
+    /// 객관식은 선택지 라벨을, 서술형은 작성한 답을 반환함
+    pub fn value(&self) -> &str {
+        match self {
+            SubmittedAnswer::Choice { label } => label,
+            SubmittedAnswer::Subjective { value, .. } | SubmittedAnswer::Numeric { value, .. } => value,
         }
     }
 }
+
+impl Matching {
+    /// 작성한 답이 정답들 중 하나와 같은지 확인함
+    pub fn accepts(&self, values: &[&str], text: &str) -> bool {
+        let text = text.trim();
+        match self {
+            Matching::Exact => values.iter().any(|v| v.trim().eq_ignore_ascii_case(text)),
+            Matching::Whitespace => {
+                let text = remove_whitespace(text);
+                values.iter().any(|v| remove_whitespace(v).eq_ignore_ascii_case(&text))
+            }
+            Matching::Numeric { tolerance } => values
+                .iter()
+                .filter_map(|v| v.trim().parse::<f64>().ok())
+                .any(|v| numbers_match(v, *tolerance, text)),
+            // 문제 파일을 읽을 때 정규식을 검사하므로 여기서는 실패하지 않음
+            Matching::Regex => values.iter().any(|v| match whole_regex(v) {
+                Ok(regex) => regex.is_match(text),
+                Err(e) => {
+                    error!("Invalid regex {}: {}", v, e);
+                    false
+                }
+            }),
+        }
+    }
+}
+
+/// 답 전체와 비교하는 정규식. 채점할 때마다 다시 컴파일하지 않도록 컴파일한 정규식을 보관함.
+fn whole_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    static REGEXES: OnceLock<Mutex<HashMap<String, Regex>>> = OnceLock::new();
+    let mut regexes = REGEXES.get_or_init(Default::default).lock().unwrap();
+    if let Some(regex) = regexes.get(pattern) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(&format!("^(?:{})$", pattern))?;
+    regexes.insert(pattern.to_string(), regex.clone());
+    Ok(regex)
+}
+
+/// 작성한 답을 숫자로 읽어 정답과의 차이가 tolerance 이하인지 확인함
+fn numbers_match(expected: f64, tolerance: f64, text: &str) -> bool {
+    let Ok(number) = text.trim().parse::<f64>() else {
+        return false;
+    };
+    // 소수를 더할 때 생기는 오차 때문에 경계값이 틀리지 않도록 약간의 여유를 둠
+    (expected - number).abs() <= tolerance + f64::EPSILON * expected.abs().max(1.0)
+}
+
+fn normalize_output(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_end().to_string()
+}
+
+/// 답 뒤에 단위까지 적었으면 단위를 떼어냄
+fn strip_unit<'a>(text: &'a str, unit: &str) -> &'a str {
+    let text = text.trim();
+    match unit.is_empty() {
+        true => text,
+        false => text.strip_suffix(unit).unwrap_or(text),
+    }
+}
+
+fn remove_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
@@ -1,23 +1,81 @@
 #![warn(clippy::all)]
 
+use axum::extract::ws::WebSocketUpgrade;
 use axum::extract::{Path, Query, State};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse};
-use axum::routing::{get, put};
-use axum::{Json, Router};
-use std::net::{Ipv4Addr, SocketAddrV4};
-use std::str::FromStr;
+use axum::routing::{delete, get, post, put};
+use axum::{Form, Json, Router};
+use clap::{Parser, Subcommand};
+use question_format::compile::{check_code, expected_output};
+use question_format::load_directory;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use study_test::{handle_submit, oauth_redirect, render_finish_page, render_question, render_redirect, serve_file, try_init_discord, OauthRedirectUrlParams, ServiceState, UserCookie, FIVE_MINUTES, QUESTIONS};
+use study_test::admin::{self, InvalidateRequest};
+use study_test::classroom::{self, ControlLogin};
+use study_test::config::Config;
+use study_test::question_loader::{load_questions, watch_questions};
+use study_test::item_analysis::analyze;
+use study_test::leaderboard::Leaderboard;
+use study_test::submission_store::open_store;
+use study_test::{check_answer, find_question, finish_session, get_session, handle_submit, get_leaderboard, leaderboard_events, oauth_redirect, render_leaderboard_page, save_session_answer, serve_file, start_session, try_init_discord, CheckRequest, LeaderboardQuery, OauthRedirectUrlParams, Question, QuestionSet, ServiceState, QUESTIONS};
 use tokio::net::TcpListener;
+use tokio::sync::{RwLock, broadcast};
 use tokio::time::sleep;
 use tower_http::trace::TraceLayer;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{fmt, EnvFilter};
 
+#[derive(Parser)]
+#[command(about = "코딩랩 Rust 스터디 퀴즈 서버")]
+struct Cli {
+    /// 설정 파일 경로. 파일이 없으면 기본값을 사용함.
+    #[arg(short, long, default_value = "quiz.toml")]
+    config: PathBuf,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 퀴즈 서버를 실행함 (기본값)
+    Serve {
+        /// 설정 파일의 bind_address 대신 사용할 주소
+        address: Option<SocketAddr>,
+    },
+    /// 문제 폴더의 모든 파일이 올바른 형식인지 검사함
+    Validate {
+        /// 검사할 폴더. 없으면 설정 파일의 questions_dir이나 questions를 사용함.
+        path: Option<PathBuf>,
+        /// 코드 블록을 rustc로 컴파일해 보지 않고 형식만 검사함
+        #[arg(long)]
+        no_compile: bool,
+    },
+    /// 문제와 정답을 JSON으로 내보냄
+    Export {
+        /// 내보낼 문제 폴더. 없으면 실행 파일에 포함된 문제를 내보냄.
+        #[arg(long)]
+        questions: Option<PathBuf>,
+        /// 출력 파일. 없으면 표준 출력에 씀.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 멤버별 최신 제출 기록으로 문제마다 정답률, 변별도, 선택지별 선택 비율을 출력함
+    Items {
+        /// 분석할 문제 폴더. 없으면 실행 파일에 포함된 문제를 분석함.
+        #[arg(long)]
+        questions: Option<PathBuf>,
+        /// 표 대신 JSON으로 출력함
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::registry()
@@ -25,33 +83,239 @@ async fn main() {
         .with(EnvFilter::from_default_env())
         .init();
 
-    let pre_rendered_questions: Vec<_> = QUESTIONS
-        .iter()
-        .enumerate()
-        .map(|(i, v)| render_question(v, i))
-        .collect();
-    let pre_rendered_redirect = render_redirect();
+    let cli = Cli::parse();
+    let mut config = match Config::load(&cli.config) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match cli.command.unwrap_or(Command::Serve { address: None }) {
+        Command::Serve { address } => {
+            if let Some(address) = address {
+                config.bind_address = address;
+            }
+            serve(config).await
+        }
+        Command::Validate { path, no_compile } => {
+            let path = path
+                .or(config.questions_dir)
+                .unwrap_or_else(|| PathBuf::from("questions"));
+            validate(path, !no_compile)
+        }
+        Command::Export { questions, output } => export(questions, output),
+        Command::Items { questions, json } => items(&config, questions, json),
+    }
+}
+
+fn validate(path: PathBuf, compile: bool) {
+    let questions = match load_directory(&path) {
+        Ok(v) => v,
+        Err(errors) => {
+            for e in &errors {
+                eprintln!("{}/{}", path.display(), e);
+            }
+            eprintln!("{}: {} problems found", path.display(), errors.len());
+            std::process::exit(1);
+        }
+    };
+
+    // 코드 블록에 적은 compile_fail, output=...이 실제 컴파일러의 결과와 같은지,
+    // 실행 결과를 정답으로 사용하는 문제의 코드가 실행되는지 확인함
+    let mut problems = 0;
+    if compile {
+        for file in &questions {
+            let result = check_code(&file.question.code, &file.question.code_check)
+                .and_then(|_| expected_output(&file.question));
+            if let Err(e) = result {
+                eprintln!("{}/{}: {}", path.display(), file.file_name, e);
+                problems += 1;
+            }
+        }
+    }
+    if problems > 0 {
+        eprintln!("{}: {} problems found", path.display(), problems);
+        std::process::exit(1);
+    }
+    println!("{}: {} questions OK", path.display(), questions.len());
+}
+
+/// 문제 폴더를 지정하지 않으면 실행 파일에 포함된 문제를 사용함
+fn questions_or_exit(questions_dir: Option<PathBuf>) -> &'static [Question] {
+    match questions_dir {
+        None => QUESTIONS,
+        Some(path) => match load_questions(&path) {
+            Ok(v) => v,
+            Err(errors) => {
+                for e in errors {
+                    eprintln!("{}/{}", path.display(), e);
+                }
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+fn export(questions_dir: Option<PathBuf>, output: Option<PathBuf>) {
+    let questions = questions_or_exit(questions_dir);
+    let json = serde_json::to_string_pretty(questions).unwrap();
+    match output {
+        None => println!("{}", json),
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                eprintln!("Unable to write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn items(config: &Config, questions_dir: Option<PathBuf>, json: bool) {
+    let questions = questions_or_exit(questions_dir);
+    let submissions = match open_store(config).and_then(|v| v.latest_per_member()) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Unable to read submissions: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let reports = analyze(&submissions, questions);
 
-    let discord_data = try_init_discord().await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+        return;
+    }
+    println!("{} submissions", submissions.len());
+    for report in reports {
+        println!();
+        println!(
+            "{} ({}) {}",
+            report.question,
+            report.file.unwrap_or("missing"),
+            report.name.unwrap_or_default()
+        );
+        let discrimination = report
+            .discrimination
+            .map(|v| format!("{:.2}", v))
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "  responses {}, difficulty {:.1}%, discrimination {}",
+            report.responses, report.difficulty, discrimination
+        );
+        for choice in report.choices {
+            let mark = if choice.answer { "*" } else { " " };
+            println!(
+                "  {} {}. {}: {} ({:.1}%)",
+                mark,
+                choice.index + 1,
+                choice.label,
+                choice.count,
+                choice.rate
+            );
+        }
+        for cluster in report.subjective {
+            let mark = if cluster.accepted { "*" } else { " " };
+            let variants: Vec<String> = cluster
+                .variants
+                .iter()
+                .map(|v| format!("\"{}\" x{}", v.text, v.count))
+                .collect();
+            println!("  {} \"{}\": {} [{}]", mark, cluster.normalized, cluster.count, variants.join(", "));
+        }
+    }
+}
+
+async fn serve(config: Config) {
+    // 개발 중에는 questions_dir을 지정하면 다시 빌드하지 않고도 문제 수정 사항이 바로 반영됨
+    let questions_dir = config.questions_dir.clone();
+    let questions = match questions_dir.as_ref() {
+        None => QUESTIONS,
+        Some(path) => match load_questions(path) {
+            Ok(v) => {
+                info!("Loaded {} questions from {}", v.len(), path.display());
+                v
+            }
+            Err(errors) => {
+                for e in errors {
+                    error!("Unable to load {}: {}", path.display(), e);
+                }
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let discord_data = try_init_discord(&config).await;
     if discord_data.is_none() {
         warn!("Discord support disabled!");
     }
-    let pre_rendered_finish_page = render_finish_page(discord_data.clone());
+    let submission_store = match open_store(&config) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Unable to open submission store: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let leaderboard = match Leaderboard::load(submission_store.clone()).await {
+        Ok(v) => {
+            info!("Loaded {} members into the leaderboard", v.len());
+            v
+        }
+        Err(e) => {
+            error!("Unable to load submissions for the leaderboard: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let question_set = QuestionSet::new(questions, discord_data.clone(), config.title.as_str());
     let state = ServiceState {
-        pre_rendered_questions,
-        pre_rendered_redirect,
+        config,
+        question_set: RwLock::new(Arc::new(question_set)),
+        sessions: Default::default(),
+        submission_store,
+        leaderboard: RwLock::new(leaderboard),
+        leaderboard_updates: broadcast::channel(64).0,
+        classroom: Default::default(),
+        admin_sessions: Default::default(),
         discord_data,
-        pre_rendered_finish_page,
     };
     let state = Arc::new(state);
 
+    if let Some(path) = questions_dir {
+        tokio::spawn(watch_questions(state.clone(), path));
+    }
+
+    {
+        let state = state.clone();
+
+        let session_cleaner = async move || {
+            let state = state;
+            let session_lifetime = state.config.time_limits.session_lifetime();
+            loop {
+                let cutoff = Instant::now() - session_lifetime;
+                {
+                    let mut writer = state.sessions.write().await;
+                    writer.retain(|_, session| session.started >= cutoff);
+                }
+                {
+                    let mut writer = state.admin_sessions.write().await;
+                    writer.retain(|_, session| session.started >= cutoff);
+                }
+                state.classroom.remove_expired(cutoff).await;
+                sleep(Duration::from_mins(1)).await;
+            }
+        };
+
+        tokio::spawn(session_cleaner());
+    }
+
     if let Some(discord_data) = state.discord_data.as_ref() {
         let discord_data = discord_data.clone();
 
         let oauth_state_cleaner = async move || {
             let discord_data = discord_data;
             loop {
-                let cutoff = Instant::now() - FIVE_MINUTES;
+                let cutoff = Instant::now() - discord_data.oauth_timeout;
                 {
                     let mut writer = discord_data.oauth_attempts.write().await;
                     writer.retain(|_, &mut (requested_time, _)| requested_time >= cutoff);
@@ -67,11 +331,46 @@ async fn main() {
         .route(
             "/",
             get(async |State(state): State<Arc<ServiceState>>| {
-                state.pre_rendered_redirect.clone().into_response()
+                start_session(&state).await
+            }),
+        )
+        .route(
+            "/session",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                get_session(&state, &headers).await
             }),
         )
+        .route(
+            "/session/finish",
+            post(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                finish_session(&state, &headers).await
+            }),
+        )
+        .route(
+            "/session/{question}",
+            put(
+                async |State(state): State<Arc<ServiceState>>,
+                       headers: HeaderMap,
+                       path: Path<String>,
+                       Json(request): Json<CheckRequest>| {
+                    save_session_answer(&state, &headers, path.0.as_str(), request).await
+                },
+            ),
+        )
+        .route(
+            "/q/{question}/check",
+            post(
+                async |State(state): State<Arc<ServiceState>>,
+                       headers: HeaderMap,
+                       path: Path<String>,
+                       Json(request): Json<CheckRequest>| {
+                    check_answer(&state, &headers, path.0.as_str(), request).await
+                },
+            ),
+        )
         .route("/favicon.png", get(|| serve_file("favicon.png")))
         .route("/Miracode.ttf", get(|| serve_file("Miracode.ttf")))
+        .route("/highlight.css", get(|| serve_file("highlight.css")))
         .route(
             "/PretendardVariable.woff2",
             get(|| serve_file("PretendardVariable.woff2")),
@@ -79,15 +378,15 @@ async fn main() {
         .route(
             "/finish",
             get(async |State(state): State<Arc<ServiceState>>| {
-                state.pre_rendered_finish_page.clone().into_response()
+                state.question_set().await.pre_rendered_finish_page.clone().into_response()
             }),
         )
         .route(
             "/submit",
-            put(async |State(state): State<Arc<ServiceState>>, Json(cookie): Json<UserCookie>| {
+            put(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
                 match state.discord_data.as_ref() {
                     None => StatusCode::NOT_FOUND.into_response(),
-                    Some(v) => handle_submit(v.clone(), cookie).await
+                    Some(v) => handle_submit(v.clone(), &state, &headers).await
                 }
             }),
         )
@@ -97,24 +396,118 @@ async fn main() {
                 async |param: Query<OauthRedirectUrlParams>, State(state): State<Arc<ServiceState>>| {
                     match state.discord_data.as_ref() {
                         None => StatusCode::NOT_FOUND.into_response(),
-                        Some(v) => oauth_redirect(param.0, v.clone()).await.into_response()
+                        Some(v) => oauth_redirect(param.0, v.clone(), &state).await.into_response()
                     }
 
                 },
             ),
         )
+        .route(
+            "/leaderboard",
+            get(async |State(state): State<Arc<ServiceState>>| {
+                render_leaderboard_page(state.config.title.as_str())
+            }),
+        )
+        .route(
+            "/leaderboard.json",
+            get(async |State(state): State<Arc<ServiceState>>, Query(query): Query<LeaderboardQuery>| {
+                get_leaderboard(&state, query).await
+            }),
+        )
+        .route(
+            "/leaderboard/events",
+            get(async |State(state): State<Arc<ServiceState>>| {
+                leaderboard_events(state).await
+            }),
+        )
+        .route(
+            "/classroom",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                classroom::participant_page(&state, &headers).await
+            }),
+        )
+        .route(
+            "/classroom/ws",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap, upgrade: WebSocketUpgrade| {
+                classroom::participant_socket(state, &headers, upgrade).await
+            }),
+        )
+        .route(
+            "/classroom/control",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                classroom::control_page(&state, &headers).await
+            })
+            .post(async |State(state): State<Arc<ServiceState>>, Form(request): Form<ControlLogin>| {
+                classroom::control_login(&state, request).await
+            }),
+        )
+        .route(
+            "/classroom/control/ws",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap, upgrade: WebSocketUpgrade| {
+                classroom::control_socket(state, &headers, upgrade).await
+            }),
+        )
+        .route(
+            "/admin",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                admin::page(&state, &headers).await
+            }),
+        )
+        .route(
+            "/admin/login",
+            get(async |State(state): State<Arc<ServiceState>>| {
+                admin::login(&state).await
+            }),
+        )
+        .route(
+            "/admin/items",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                admin::items_page(&state, &headers).await
+            }),
+        )
+        .route(
+            "/admin/api/items",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                admin::item_report(&state, &headers).await
+            }),
+        )
+        .route(
+            "/admin/api/submissions",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap| {
+                admin::list_submissions(&state, &headers).await
+            }),
+        )
+        .route(
+            "/admin/api/submissions/{id}",
+            get(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap, path: Path<String>| {
+                admin::get_submission(&state, &headers, path.0).await
+            })
+            .merge(delete(async |State(state): State<Arc<ServiceState>>, headers: HeaderMap, path: Path<String>| {
+                admin::delete_submission(&state, &headers, path.0).await
+            })),
+        )
+        .route(
+            "/admin/api/submissions/{id}/invalidated",
+            put(
+                async |State(state): State<Arc<ServiceState>>,
+                       headers: HeaderMap,
+                       path: Path<String>,
+                       Json(request): Json<InvalidateRequest>| {
+                    admin::set_invalidated(&state, &headers, path.0, request).await
+                },
+            ),
+        )
         // .route("/submit", put())
         .route(
-            "/{question}",
+            "/q/{question}",
             get(
-                |State(state): State<Arc<ServiceState>>, path: Path<usize>| {
-                    let index = path.0;
-                    async move {
-                        match state.pre_rendered_questions.get(index) {
-                            Some(v) => v.clone().into_response(),
-                            None => {
-                                StatusCode::NOT_FOUND.into_response()
-                            }
+                async |State(state): State<Arc<ServiceState>>, path: Path<String>| {
+                    let question_set = state.question_set().await;
+                    let index = find_question(question_set.questions, path.0.as_str());
+                    match index.and_then(|v| question_set.pre_rendered_questions.get(v)) {
+                        Some(v) => v.clone().into_response(),
+                        None => {
+                            StatusCode::NOT_FOUND.into_response()
                         }
                     }
                 },
@@ -122,11 +515,7 @@ async fn main() {
         )
         .layer(TraceLayer::new_for_http());
 
-    let args: Vec<String> = std::env::args().collect();
-    let address: SocketAddrV4 = args
-        .get(1)
-        .map(|v| SocketAddrV4::from_str(v.as_str()).expect("Invalid Socket Address"))
-        .unwrap_or(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 8080));
+    let address = state.config.bind_address;
     info!("Serving at {}", address);
     let listener = TcpListener::bind(address).await.unwrap();
     axum::serve(listener, app.with_state(state)).await.unwrap();
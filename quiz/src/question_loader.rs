@@ -0,0 +1,127 @@
+//! 개발용 문제 로더. 빌드 시간에 포함된 문제 대신 디렉토리에서 문제를 읽고,
+//! 파일이 바뀌면 다시 읽어서 페이지를 새로 만듦.
+//!
+//! 읽은 문제는 `&'static`으로 사용하기 위해 leak 하기 때문에, 다시 읽을 때마다 메모리가 조금씩 늘어남.
+//! 개발 중에만 사용해야 함.
+
+use crate::{Answer, Matching, Question, QuestionSet, ServiceState};
+use question_format::prepare::{PreparedChoice, PreparedQuestion, prepare_directory};
+use question_format::{LoadError, MatchRule};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+pub fn load_questions(path: &Path) -> Result<&'static [Question], Vec<LoadError>> {
+    let questions: Vec<Question> = prepare_directory(path)?.into_iter().map(leak_question).collect();
+    Ok(questions.leak())
+}
+
+/// 디렉토리를 주기적으로 확인해 문제 파일이 바뀌면 다시 읽음.
+/// 새 파일에 오류가 있으면 오류를 출력하고 이전 문제 목록을 계속 사용함.
+/// 파일을 읽고 코드 블록을 컴파일하고 실행하는 동안 다른 요청이 멈추지 않도록 별도의 스레드에서 실행함.
+pub async fn watch_questions(state: Arc<ServiceState>, path: PathBuf) {
+    let path = Arc::new(path);
+    let mut last_fingerprint = blocking(&path, fingerprint).await;
+    loop {
+        sleep(Duration::from_secs(1)).await;
+
+        let current_fingerprint = blocking(&path, fingerprint).await;
+        if current_fingerprint == last_fingerprint {
+            continue;
+        }
+        last_fingerprint = current_fingerprint;
+
+        match blocking(&path, load_questions).await {
+            Ok(questions) => {
+                let question_set = QuestionSet::new(
+                    questions,
+                    state.discord_data.clone(),
+                    state.config.title.as_str(),
+                );
+                *state.question_set.write().await = Arc::new(question_set);
+                info!("Reloaded {} questions from {}", questions.len(), path.display());
+            }
+            Err(errors) => {
+                for e in errors {
+                    error!("Unable to reload {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+/// 경로를 받는 동기 함수를 spawn_blocking으로 실행함
+async fn blocking<T: Send + 'static>(
+    path: &Arc<PathBuf>,
+    task: impl FnOnce(&Path) -> T + Send + 'static,
+) -> T {
+    let path = path.clone();
+    spawn_blocking(move || task(&path)).await.unwrap()
+}
+
+/// 파일 이름, 수정 시간, 크기로 디렉토리의 변경 여부를 판단함
+fn fingerprint(path: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    let Ok(directory) = std::fs::read_dir(path) else {
+        return vec![];
+    };
+
+    let mut entries: Vec<_> = directory
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((entry.path(), metadata.modified().ok(), metadata.len()))
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// 준비한 문제를 `&'static`으로 사용하도록 leak 함
+fn leak_question(question: PreparedQuestion) -> Question {
+    let choices: Vec<Answer> = question
+        .choices
+        .into_iter()
+        .map(|choice| match choice {
+            PreparedChoice::Choice { label } => Answer::Choice { label: label.leak() },
+            PreparedChoice::Step { label } => Answer::Step { label: label.leak() },
+            PreparedChoice::Output { label, output } => Answer::Output {
+                label: label.leak(),
+                output: output.leak(),
+            },
+            PreparedChoice::Numeric { label, value, tolerance, unit } => Answer::Numeric {
+                label: label.leak(),
+                value,
+                tolerance,
+                unit: unit.leak(),
+            },
+            PreparedChoice::Subjective { label, values } => {
+                let values: Vec<&'static str> = values.into_iter().map(|v| &*v.leak()).collect();
+                Answer::Subjective {
+                    label: label.leak(),
+                    values: values.leak(),
+                }
+            }
+        })
+        .collect();
+
+    Question {
+        id: question.id.leak(),
+        file: question.file.leak(),
+        name: question.name.leak(),
+        description: question.description.leak(),
+        code: question.code.leak(),
+        code_html: question.code_html.leak(),
+        choices: choices.leak(),
+        answer: question.answer.leak(),
+        explanation: question.explanation.leak(),
+        matching: match question.matching {
+            MatchRule::Exact => Matching::Exact,
+            MatchRule::Whitespace => Matching::Whitespace,
+            MatchRule::Numeric { tolerance } => Matching::Numeric { tolerance },
+            MatchRule::Regex => Matching::Regex,
+        },
+    }
+}
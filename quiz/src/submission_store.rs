@@ -0,0 +1,607 @@
+//! 디스코드로 제출한 채점 결과를 저장하는 곳.
+//! 기존의 폴더 구조를 그대로 쓰는 [`FileSubmissionStore`]와 SQLite 파일 하나에 저장하는 [`SqliteSubmissionStore`]가 있음.
+//!
+//! 저장소는 동기 API이므로 비동기 코드에서는 `spawn_blocking` 안에서 호출해야 함.
+
+use crate::config::{Config, SubmissionBackend};
+use crate::{GradedAnswer, QuizResult};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// 파일 이름에 사용하는 제출 시간 형식
+const FILE_TIME_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
+/// 무효 처리한 제출 기록은 `{제출 시간}.invalid.json`으로 이름을 바꿈
+const INVALID_SUFFIX: &str = ".invalid.json";
+/// 멤버 폴더 안에서 마지막으로 제출할 때의 닉네임을 내용으로 저장하는 파일
+const USERNAME_FILE: &str = "username.txt";
+
+/// 제출 기록 하나
+#[derive(Debug, Serialize)]
+pub struct Submission {
+    /// 저장소가 정한 id. 저장하기 전에는 빈 문자열임.
+    pub id: String,
+    /// 디스코드 사용자 id
+    pub member_id: String,
+    /// 제출할 때의 서버 닉네임
+    pub username: String,
+    pub submitted_at: DateTime<Local>,
+    pub result: QuizResult,
+    /// 관리자가 무효 처리한 제출. 순위와 문제별 기록에서 제외됨.
+    pub invalidated: bool,
+}
+
+/// 특정 문제에 대한 제출 기록 하나
+#[derive(Debug, Serialize)]
+pub struct QuestionSubmission {
+    pub member_id: String,
+    pub username: String,
+    pub submitted_at: DateTime<Local>,
+    pub answer: GradedAnswer,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(PathBuf, std::io::Error),
+    /// 폴더 이름으로 쓸 수 없는 멤버 id
+    InvalidMemberId(String),
+    /// 저장된 내용을 읽을 수 없음. 첫 번째 값은 파일 경로나 테이블 이름임.
+    Corrupted(String, String),
+    Sqlite(rusqlite::Error),
+}
+
+pub trait SubmissionStore: Send + Sync {
+    /// 제출 기록을 저장하고 새 id를 반환함
+    fn save(&self, submission: &Submission) -> Result<String, StoreError>;
+
+    /// 모든 제출 기록을 제출한 순서로 반환함. 무효 처리한 기록도 포함됨.
+    fn submissions(&self) -> Result<Vec<Submission>, StoreError>;
+
+    /// 멤버마다 가장 마지막으로 제출한 유효한 기록
+    fn latest_per_member(&self) -> Result<Vec<Submission>, StoreError>;
+
+    /// 한 문제에 대한 모든 유효한 제출 기록. 같은 멤버가 여러 번 제출했으면 모두 포함됨.
+    fn submissions_for_question(&self, question: &str) -> Result<Vec<QuestionSubmission>, StoreError>;
+
+    fn get(&self, id: &str) -> Result<Option<Submission>, StoreError>;
+
+    /// 제출 기록을 지움. 해당하는 기록이 없으면 false를 반환함.
+    fn delete(&self, id: &str) -> Result<bool, StoreError>;
+
+    /// 제출 기록을 무효 처리하거나 되돌림. 해당하는 기록이 없으면 false를 반환함.
+    fn set_invalidated(&self, id: &str, invalidated: bool) -> Result<bool, StoreError>;
+}
+
+/// 설정에 따라 저장소를 만듦
+pub fn open_store(config: &Config) -> Result<Arc<dyn SubmissionStore>, StoreError> {
+    Ok(match config.submission_store {
+        SubmissionBackend::Filesystem => Arc::new(FileSubmissionStore::new(config.submission_dir.clone())),
+        SubmissionBackend::Sqlite => Arc::new(SqliteSubmissionStore::open(&config.submission_db)?),
+    })
+}
+
+/// `{submission_dir}/{member_id}/{제출 시간}.json`에 결과를, 같은 폴더의 `username.txt`에 닉네임을 저장함.
+/// 닉네임은 사용자가 정하는 값이므로 경로에 사용하지 않음.
+/// 제출 기록의 id는 `{member_id}_{제출 시간}`임.
+pub struct FileSubmissionStore {
+    root: PathBuf,
+}
+
+impl FileSubmissionStore {
+    pub fn new(root: PathBuf) -> Self {
+        FileSubmissionStore { root }
+    }
+
+    /// 멤버 폴더 하나의 모든 제출 기록을 시간 순서로 읽음
+    fn read_member(&self, dir: &Path, member_id: &str) -> Result<Vec<Submission>, StoreError> {
+        let mut results = vec![];
+        // 예전 버전은 닉네임 이름의 빈 파일을 남겼으므로 username.txt가 없으면 가장 최근에 만든 것을 사용함
+        let mut username = None;
+        let mut legacy_username = None;
+        for entry in fs::read_dir(dir).map_err(|e| StoreError::Io(dir.to_path_buf(), e))? {
+            let entry = entry.map_err(|e| StoreError::Io(dir.to_path_buf(), e))?;
+            let path = entry.path();
+            let Some(file_name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+
+            let (time, invalidated) = if file_name == USERNAME_FILE {
+                let contents = fs::read_to_string(&path).map_err(|e| StoreError::Io(path.clone(), e))?;
+                username = Some(contents.trim_end_matches('\n').to_string());
+                continue;
+            } else if let Some(time) = file_name.strip_suffix(INVALID_SUFFIX) {
+                (time, true)
+            } else if let Some(time) = file_name.strip_suffix(".json") {
+                (time, false)
+            } else {
+                let modified = entry.metadata().and_then(|v| v.modified()).ok();
+                if legacy_username.as_ref().is_none_or(|(time, _)| modified > *time) {
+                    legacy_username = Some((modified, file_name));
+                }
+                continue;
+            };
+            let Some(submitted_at) = NaiveDateTime::parse_from_str(time, FILE_TIME_FORMAT)
+                .ok()
+                .and_then(|v| Local.from_local_datetime(&v).earliest())
+            else {
+                continue;
+            };
+
+            let contents = fs::read_to_string(&path).map_err(|e| StoreError::Io(path.clone(), e))?;
+            let result = serde_json::from_str::<StoredResult>(&contents)
+                .map_err(|e| StoreError::Corrupted(path.display().to_string(), e.to_string()))?
+                .into();
+            results.push(Submission {
+                id: format!("{}_{}", member_id, time),
+                member_id: member_id.to_string(),
+                username: String::new(),
+                submitted_at,
+                result,
+                invalidated,
+            });
+        }
+
+        let username = username
+            .or_else(|| legacy_username.map(|(_, v)| v))
+            .unwrap_or_default();
+        for submission in results.iter_mut() {
+            submission.username.clone_from(&username);
+        }
+        results.sort_by_key(|v| v.submitted_at);
+        Ok(results)
+    }
+
+    fn read_all(&self) -> Result<Vec<Vec<Submission>>, StoreError> {
+        let directory = match fs::read_dir(&self.root) {
+            Ok(v) => v,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(StoreError::Io(self.root.clone(), e)),
+        };
+
+        let mut members = vec![];
+        for entry in directory {
+            let entry = entry.map_err(|e| StoreError::Io(self.root.clone(), e))?;
+            if !entry.file_type().is_ok_and(|v| v.is_dir()) {
+                continue;
+            }
+            let Some(member_id) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            members.push(self.read_member(&entry.path(), &member_id)?);
+        }
+        Ok(members)
+    }
+}
+
+impl SubmissionStore for FileSubmissionStore {
+    fn save(&self, submission: &Submission) -> Result<String, StoreError> {
+        if !valid_member_id(&submission.member_id) {
+            return Err(StoreError::InvalidMemberId(submission.member_id.clone()));
+        }
+        let json_string = serde_json::to_string_pretty(&submission.result).unwrap();
+        let dir_path = self.root.join(&submission.member_id);
+        let username_path = dir_path.join(USERNAME_FILE);
+        let time = submission.submitted_at.format(FILE_TIME_FORMAT);
+        let submission_path = dir_path.join(format!("{}.json", time));
+
+        fs::create_dir_all(&dir_path).map_err(|e| StoreError::Io(dir_path.clone(), e))?;
+        remove_legacy_markers(&dir_path);
+        if let Err(e) = fs::write(&username_path, &submission.username) {
+            error!(
+                "Unable to write username file at {}!\n{:?}\nUsername \"{}\" is not saved!",
+                username_path.display(),
+                e,
+                submission.username
+            );
+        }
+        fs::write(&submission_path, json_string).map_err(|e| StoreError::Io(submission_path, e))?;
+        Ok(format!("{}_{}", submission.member_id, time))
+    }
+
+    fn submissions(&self) -> Result<Vec<Submission>, StoreError> {
+        let mut submissions: Vec<Submission> = self.read_all()?.into_iter().flatten().collect();
+        submissions.sort_by_key(|v| v.submitted_at);
+        Ok(submissions)
+    }
+
+    fn latest_per_member(&self) -> Result<Vec<Submission>, StoreError> {
+        Ok(self
+            .read_all()?
+            .into_iter()
+            .filter_map(|v| v.into_iter().rfind(|v| !v.invalidated))
+            .collect())
+    }
+
+    fn submissions_for_question(&self, question: &str) -> Result<Vec<QuestionSubmission>, StoreError> {
+        let mut results = vec![];
+        for submission in self.read_all()?.into_iter().flatten().filter(|v| !v.invalidated && !v.result.legacy) {
+            let Submission { member_id, username, submitted_at, result, .. } = submission;
+            results.extend(
+                result
+                    .answers
+                    .into_iter()
+                    .filter(|v| v.question == question)
+                    .map(|answer| QuestionSubmission {
+                        member_id: member_id.clone(),
+                        username: username.clone(),
+                        submitted_at,
+                        answer,
+                    }),
+            );
+        }
+        results.sort_by_key(|v| v.submitted_at);
+        Ok(results)
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Submission>, StoreError> {
+        let Some((member_id, _)) = parse_file_id(id) else {
+            return Ok(None);
+        };
+        let dir = self.root.join(member_id);
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        Ok(self.read_member(&dir, member_id)?.into_iter().find(|v| v.id == id))
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, StoreError> {
+        let Some((member_id, time)) = parse_file_id(id) else {
+            return Ok(false);
+        };
+        let dir = self.root.join(member_id);
+        for suffix in [".json", INVALID_SUFFIX] {
+            let path = dir.join(format!("{}{}", time, suffix));
+            match fs::remove_file(&path) {
+                Ok(()) => return Ok(true),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(StoreError::Io(path, e)),
+            }
+        }
+        Ok(false)
+    }
+
+    fn set_invalidated(&self, id: &str, invalidated: bool) -> Result<bool, StoreError> {
+        let Some((member_id, time)) = parse_file_id(id) else {
+            return Ok(false);
+        };
+        let dir = self.root.join(member_id);
+        let valid_path = dir.join(format!("{}.json", time));
+        let invalid_path = dir.join(format!("{}{}", time, INVALID_SUFFIX));
+        let (from, to) = match invalidated {
+            true => (valid_path, invalid_path),
+            false => (invalid_path, valid_path),
+        };
+        match fs::rename(&from, &to) {
+            Ok(()) => Ok(true),
+            // 이미 원하는 상태이면 바꿀 필요가 없음
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(to.is_file()),
+            Err(e) => Err(StoreError::Io(from, e)),
+        }
+    }
+}
+
+/// 파일에 저장된 채점 결과. 서버에서 채점하기 전의 버전은 `[파일 이름, 고른 답]` 쌍과 100점 만점의 점수만 저장했음.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredResult {
+    Graded(QuizResult),
+    Legacy { answers: Vec<(String, String)>, score: f32 },
+}
+
+impl From<StoredResult> for QuizResult {
+    fn from(value: StoredResult) -> Self {
+        match value {
+            StoredResult::Graded(result) => result,
+            StoredResult::Legacy { answers, score } => {
+                // 지금처럼 맞힌 문제 수를 점수로 쓰도록 바꿈
+                let score = score / 100.0 * answers.len() as f32;
+                let answers = answers
+                    .into_iter()
+                    .map(|(file, answer)| GradedAnswer {
+                        question: file.strip_suffix(".md").unwrap_or(&file).to_string(),
+                        answer: vec![answer],
+                        correct: false,
+                        credit: 0.0,
+                    })
+                    .collect();
+                QuizResult { answers, score, legacy: true }
+            }
+        }
+    }
+}
+
+/// `{member_id}_{제출 시간}` 형식의 id를 나눔. 다른 폴더에 접근하지 못하도록 형식이 맞지 않으면 None을 반환함.
+fn parse_file_id(id: &str) -> Option<(&str, &str)> {
+    let (member_id, time) = id.rsplit_once('_')?;
+    let valid_time = NaiveDateTime::parse_from_str(time, FILE_TIME_FORMAT).is_ok();
+    (valid_member_id(member_id) && valid_time).then_some((member_id, time))
+}
+
+/// 멤버 id는 폴더 이름으로 쓰므로 다른 폴더를 가리킬 수 없는 영문자와 숫자만 허용함
+fn valid_member_id(member_id: &str) -> bool {
+    !member_id.is_empty() && member_id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// 예전 버전이 남긴 닉네임 이름의 빈 파일을 지움
+fn remove_legacy_markers(dir: &Path) {
+    let Ok(directory) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in directory.flatten() {
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let is_marker = !file_name.ends_with(".json")
+            && file_name != USERNAME_FILE
+            && entry.file_type().is_ok_and(|v| v.is_file());
+        if is_marker && let Err(e) = fs::remove_file(entry.path())
+        {
+            error!("Unable to remove old username file at {}!\n{:?}", entry.path().display(), e);
+        }
+    }
+}
+
+/// SQLite 파일 하나에 제출 기록을 저장함. 문제별 답안은 따로 색인해 두어 문제 하나의 기록만 빠르게 찾을 수 있음.
+pub struct SqliteSubmissionStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteSubmissionStore {
+    /// 파일이 없으면 새로 만들고 테이블을 준비함
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(
+            "PRAGMA foreign_keys = ON;
+            CREATE TABLE IF NOT EXISTS submissions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                member_id TEXT NOT NULL,
+                username TEXT NOT NULL,
+                submitted_at TEXT NOT NULL,
+                score REAL NOT NULL,
+                invalidated INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS submissions_member ON submissions (member_id, id);
+            CREATE TABLE IF NOT EXISTS answers (
+                submission_id INTEGER NOT NULL REFERENCES submissions (id),
+                position INTEGER NOT NULL,
+                question TEXT NOT NULL,
+                answer TEXT NOT NULL,
+                correct INTEGER NOT NULL,
+                credit REAL NOT NULL,
+                PRIMARY KEY (submission_id, position)
+            );
+            CREATE INDEX IF NOT EXISTS answers_question ON answers (question);",
+        )?;
+
+        // invalidated 열이 생기기 전에 만든 데이터베이스
+        let has_invalidated: bool = connection.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('submissions') WHERE name = 'invalidated'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_invalidated {
+            connection.execute_batch("ALTER TABLE submissions ADD COLUMN invalidated INTEGER NOT NULL DEFAULT 0;")?;
+        }
+
+        Ok(SqliteSubmissionStore {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// 조건에 맞는 제출 기록을 저장한 순서로 읽음
+    fn query_submissions(
+        connection: &Connection,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Submission>, StoreError> {
+        let mut statement = connection.prepare_cached(&format!(
+            "SELECT id, member_id, username, submitted_at, score, invalidated FROM submissions {} ORDER BY id",
+            filter
+        ))?;
+        let rows = statement.query_map(params, |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get::<_, String>(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })?;
+
+        let mut submissions = vec![];
+        for row in rows {
+            let (id, member_id, username, submitted_at, score, invalidated) = row?;
+            submissions.push(Submission {
+                id: id.to_string(),
+                member_id,
+                username,
+                submitted_at: decode_time(&submitted_at)?,
+                result: QuizResult {
+                    answers: Self::answers(connection, id)?,
+                    score,
+                    legacy: false,
+                },
+                invalidated,
+            });
+        }
+        Ok(submissions)
+    }
+
+    fn answers(connection: &Connection, submission_id: i64) -> Result<Vec<GradedAnswer>, StoreError> {
+        let mut statement = connection.prepare_cached(
+            "SELECT question, answer, correct, credit FROM answers WHERE submission_id = ?1 ORDER BY position",
+        )?;
+        let rows = statement.query_map([submission_id], |row| {
+            Ok((row.get(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get(3)?))
+        })?;
+
+        let mut answers = vec![];
+        for row in rows {
+            let (question, answer, correct, credit) = row?;
+            answers.push(GradedAnswer {
+                question,
+                answer: decode_answer(&answer)?,
+                correct,
+                credit,
+            });
+        }
+        Ok(answers)
+    }
+}
+
+impl SubmissionStore for SqliteSubmissionStore {
+    fn save(&self, submission: &Submission) -> Result<String, StoreError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO submissions (member_id, username, submitted_at, score, invalidated)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                submission.member_id,
+                submission.username,
+                submission.submitted_at.to_rfc3339(),
+                submission.result.score,
+                submission.invalidated
+            ],
+        )?;
+        let submission_id = transaction.last_insert_rowid();
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO answers (submission_id, position, question, answer, correct, credit)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for (position, answer) in submission.result.answers.iter().enumerate() {
+                statement.execute(params![
+                    submission_id,
+                    position,
+                    answer.question,
+                    serde_json::to_string(&answer.answer).unwrap(),
+                    answer.correct,
+                    answer.credit
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(submission_id.to_string())
+    }
+
+    fn submissions(&self) -> Result<Vec<Submission>, StoreError> {
+        let connection = self.connection.lock().unwrap();
+        Self::query_submissions(&connection, "", [])
+    }
+
+    fn latest_per_member(&self) -> Result<Vec<Submission>, StoreError> {
+        let connection = self.connection.lock().unwrap();
+        Self::query_submissions(
+            &connection,
+            "WHERE id IN (SELECT MAX(id) FROM submissions WHERE invalidated = 0 GROUP BY member_id)",
+            [],
+        )
+    }
+
+    fn submissions_for_question(&self, question: &str) -> Result<Vec<QuestionSubmission>, StoreError> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare_cached(
+            "SELECT s.member_id, s.username, s.submitted_at, a.question, a.answer, a.correct, a.credit
+            FROM answers a JOIN submissions s ON s.id = a.submission_id
+            WHERE a.question = ?1 AND s.invalidated = 0
+            ORDER BY s.id",
+        )?;
+        let rows = statement.query_map([question], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get::<_, String>(2)?,
+                row.get(3)?,
+                row.get::<_, String>(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })?;
+
+        let mut submissions = vec![];
+        for row in rows {
+            let (member_id, username, submitted_at, question, answer, correct, credit) = row?;
+            submissions.push(QuestionSubmission {
+                member_id,
+                username,
+                submitted_at: decode_time(&submitted_at)?,
+                answer: GradedAnswer {
+                    question,
+                    answer: decode_answer(&answer)?,
+                    correct,
+                    credit,
+                },
+            });
+        }
+        Ok(submissions)
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Submission>, StoreError> {
+        let Ok(id) = id.parse::<i64>() else {
+            return Ok(None);
+        };
+        let connection = self.connection.lock().unwrap();
+        Ok(Self::query_submissions(&connection, "WHERE id = ?1", [id])?.pop())
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, StoreError> {
+        let Ok(id) = id.parse::<i64>() else {
+            return Ok(false);
+        };
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM answers WHERE submission_id = ?1", [id])?;
+        let deleted = transaction.execute("DELETE FROM submissions WHERE id = ?1", [id])?;
+        transaction.commit()?;
+        Ok(deleted > 0)
+    }
+
+    fn set_invalidated(&self, id: &str, invalidated: bool) -> Result<bool, StoreError> {
+        let Ok(id) = id.parse::<i64>() else {
+            return Ok(false);
+        };
+        let connection = self.connection.lock().unwrap();
+        let updated = connection.execute(
+            "UPDATE submissions SET invalidated = ?1 WHERE id = ?2",
+            params![invalidated, id],
+        )?;
+        Ok(updated > 0)
+    }
+}
+
+fn decode_time(value: &str) -> Result<DateTime<Local>, StoreError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|v| v.with_timezone(&Local))
+        .map_err(|e| StoreError::Corrupted("submissions".to_string(), e.to_string()))
+}
+
+fn decode_answer(value: &str) -> Result<Vec<String>, StoreError> {
+    serde_json::from_str(value).map_err(|e| StoreError::Corrupted("answers".to_string(), e.to_string()))
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(value: rusqlite::Error) -> Self {
+        StoreError::Sqlite(value)
+    }
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            StoreError::InvalidMemberId(id) => write!(f, "invalid member id {:?}", id),
+            StoreError::Corrupted(location, e) => write!(f, "{}: invalid submission: {}", location, e),
+            StoreError::Sqlite(e) => write!(f, "sqlite: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
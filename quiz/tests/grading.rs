@@ -0,0 +1,125 @@
+use serde_json::json;
+use study_test::question_loader::load_questions;
+use study_test::{Matching, SubmittedAnswer, SubmittedEntry};
+
+#[test]
+fn exact_ignores_surrounding_whitespace_and_ascii_case() {
+    let values = ["Vec<u8>", "바이트"];
+    assert!(Matching::Exact.accepts(&values, "  vec<U8> "));
+    assert!(Matching::Exact.accepts(&values, "바이트"));
+    assert!(!Matching::Exact.accepts(&values, "vec <u8>"));
+    assert!(!Matching::Exact.accepts(&[], "바이트"));
+}
+
+#[test]
+fn whitespace_ignores_every_space() {
+    let values = ["4 바이트", "4"];
+    assert!(Matching::Whitespace.accepts(&values, "4바이트"));
+    assert!(Matching::Whitespace.accepts(&values, " 4  바이트"));
+    assert!(Matching::Whitespace.accepts(&values, "4"));
+    assert!(!Matching::Whitespace.accepts(&values, "4 비트"));
+}
+
+#[test]
+fn numeric_uses_tolerance() {
+    let matching = Matching::Numeric { tolerance: 0.1 };
+    assert!(matching.accepts(&["3.14"], "3.2"));
+    assert!(matching.accepts(&["3.14"], " 3.04 "));
+    assert!(!matching.accepts(&["3.14"], "3.3"));
+    assert!(!matching.accepts(&["3.14"], "파이"));
+
+    let exact = Matching::Numeric { tolerance: 0.0 };
+    assert!(exact.accepts(&["44"], "44.0"));
+    assert!(!exact.accepts(&["44"], "45"));
+}
+
+#[test]
+fn regex_matches_the_whole_answer() {
+    let values = ["4 ?바이트", "(?i)four"];
+    assert!(Matching::Regex.accepts(&values, "4바이트"));
+    assert!(Matching::Regex.accepts(&values, "4 바이트"));
+    assert!(Matching::Regex.accepts(&values, "FOUR"));
+    assert!(!Matching::Regex.accepts(&values, "14 바이트"));
+    assert!(!Matching::Regex.accepts(&values, "four bytes"));
+}
+
+#[test]
+fn numeric_answers_use_tolerance_and_unit() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(
+        directory.path().join("size.md"),
+        "# 크기\n- [x] 크기: {4 ± 0.5 바이트}\n",
+    )
+    .unwrap();
+    let questions = load_questions(directory.path()).unwrap();
+
+    let test = |text: &str| {
+        let entries: Vec<SubmittedEntry> = serde_json::from_value(json!([{ "choice": 0, "text": text }])).unwrap();
+        let answers = SubmittedAnswer::parse(&questions[0], &entries).unwrap();
+        questions[0].test(&answers)
+    };
+    assert!(test("4"));
+    assert!(test("4.5"));
+    assert!(test("3.5바이트"));
+    assert!(test(" 4 바이트 "));
+    assert!(!test("4.6"));
+    assert!(!test("네 바이트"));
+}
+
+#[test]
+fn ordering_gives_partial_credit_by_kendall_distance() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(directory.path().join("order.md"), "---\ntype: ordering\n---\n# 순서\n1. a\n2. b\n3. c\n4. d\n").unwrap();
+    let questions = load_questions(directory.path()).unwrap();
+    let question = &questions[0];
+    assert!(question.is_ordering());
+    assert_ne!(question.display_order(), vec![0, 1, 2, 3]);
+
+    let grade = |order: &[usize]| {
+        let entries: Vec<SubmittedEntry> =
+            serde_json::from_value(order.iter().map(|v| json!({ "choice": v })).collect()).unwrap();
+        let answers = SubmittedAnswer::parse(question, &entries).unwrap();
+        (question.test(&answers), question.credit(&answers))
+    };
+    assert_eq!(grade(&[0, 1, 2, 3]), (true, 1.0));
+    // 6쌍 중 한 쌍이 뒤바뀜
+    let (correct, credit) = grade(&[1, 0, 2, 3]);
+    assert!(!correct);
+    assert!((credit - 5.0 / 6.0).abs() < 1e-6);
+    assert_eq!(grade(&[3, 2, 1, 0]), (false, 0.0));
+    // 모든 항목을 나열하지 않으면 0점
+    assert_eq!(grade(&[0, 1, 2]), (false, 0.0));
+    assert_eq!(grade(&[]), (false, 0.0));
+}
+
+#[test]
+fn output_answers_are_graded_against_the_real_output() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(
+        directory.path().join("output.md"),
+        "# 출력\n```rs\nfn main() {\n    println!(\"a\");\n    println!(\"B\");\n}\n```\n- [x] 출력: [@output]\n",
+    )
+    .unwrap();
+    let questions = load_questions(directory.path()).unwrap();
+
+    let test = |text: &str| {
+        let entries: Vec<SubmittedEntry> = serde_json::from_value(json!([{ "choice": 0, "text": text }])).unwrap();
+        let answers = SubmittedAnswer::parse(&questions[0], &entries).unwrap();
+        questions[0].test(&answers)
+    };
+    assert!(test("a\nB"));
+    assert!(test("a  \r\nb\n\n"));
+    assert!(!test("a B"));
+    assert!(!test("a"));
+
+    // 실행할 수 없는 코드는 불러올 때 오류로 보고함
+    std::fs::write(
+        directory.path().join("output.md"),
+        "# 출력\n```rs\nfn main() { let x: i32 = \"1\"; }\n```\n- [x] 출력: [@output]\n",
+    )
+    .unwrap();
+    let Err(errors) = load_questions(directory.path()) else {
+        panic!("code that does not compile must be reported");
+    };
+    assert_eq!(errors[0].file.as_deref(), Some("output.md"));
+}
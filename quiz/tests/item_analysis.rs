@@ -0,0 +1,123 @@
+use chrono::{Local, TimeZone};
+use study_test::item_analysis::analyze;
+use study_test::submission_store::Submission;
+use study_test::{GradedAnswer, QUESTIONS, QuizResult};
+
+/// 선택지가 모두 객관식이고 정답이 하나인 문제의 (id, 정답 라벨, 오답 라벨)
+fn choice_questions() -> Vec<(String, String, String)> {
+    let questions = serde_json::to_value(QUESTIONS).unwrap();
+    questions
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|question| {
+            let answer = question["answer"].as_array()?;
+            let labels: Option<Vec<&str>> = question["choices"]
+                .as_array()?
+                .iter()
+                .map(|v| v["Choice"]["label"].as_str())
+                .collect();
+            let labels = labels?;
+            let answer = answer.first()?.as_u64()? as usize;
+            let wrong = (0..labels.len()).find(|&v| v != answer)?;
+            (question["answer"].as_array()?.len() == 1).then(|| {
+                (
+                    question["id"].as_str().unwrap().to_string(),
+                    labels[answer].to_string(),
+                    labels[wrong].to_string(),
+                )
+            })
+        })
+        .collect()
+}
+
+fn submission(member_id: &str, answers: Vec<(&str, &str, bool)>) -> Submission {
+    let answers: Vec<GradedAnswer> = answers
+        .into_iter()
+        .map(|(question, answer, correct)| GradedAnswer {
+            question: question.to_string(),
+            answer: vec![answer.to_string()],
+            correct,
+            credit: if correct { 1.0 } else { 0.0 },
+        })
+        .collect();
+    let score = answers.iter().map(|v| v.credit).sum::<f32>() / 2.0 * 100.0;
+    Submission {
+        id: String::new(),
+        member_id: member_id.to_string(),
+        username: member_id.to_string(),
+        submitted_at: Local.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap(),
+        result: QuizResult { answers, score, legacy: false },
+        invalidated: false,
+    }
+}
+
+#[test]
+fn difficulty_discrimination_and_choices() {
+    let questions = choice_questions();
+    let (a, a_right, a_wrong) = &questions[0];
+    let (b, b_right, b_wrong) = &questions[1];
+
+    let submissions = [
+        submission("top", vec![(a, a_right, true), (b, b_right, true), ("deleted", "x", true)]),
+        submission("middle1", vec![(a, a_right, true), (b, b_wrong, false)]),
+        submission("bottom", vec![(a, a_wrong, false), (b, b_wrong, false)]),
+        submission("middle2", vec![(a, a_wrong, false), (b, b_right, true)]),
+    ];
+    let reports = analyze(&submissions, QUESTIONS);
+    assert_eq!(reports.len(), QUESTIONS.len() + 1);
+
+    let report = reports.iter().find(|v| v.question == *a).unwrap();
+    assert_eq!(report.responses, 4);
+    assert_eq!(report.difficulty, 50.0);
+    // 상위 집단(top)은 맞혔고 하위 집단(bottom)은 틀림
+    assert_eq!(report.discrimination, Some(1.0));
+    assert!(report.file.unwrap().ends_with(".md"));
+    let counts: Vec<(bool, usize)> = report
+        .choices
+        .iter()
+        .filter(|v| v.count > 0)
+        .map(|v| (v.answer, v.count))
+        .collect();
+    assert_eq!(counts.len(), 2);
+    assert!(counts.contains(&(true, 2)));
+    assert!(counts.contains(&(false, 2)));
+
+    let deleted = reports.last().unwrap();
+    assert_eq!(deleted.question, "deleted");
+    assert_eq!((deleted.file, deleted.responses), (None, 1));
+    assert_eq!(deleted.discrimination, None);
+}
+
+#[test]
+fn empty_report() {
+    let reports = analyze(&[], QUESTIONS);
+    assert_eq!(reports.len(), QUESTIONS.len());
+    assert!(reports.iter().all(|v| v.responses == 0 && v.discrimination.is_none()));
+    assert!(reports.iter().all(|v| v.choices.iter().all(|c| c.count == 0)));
+}
+
+#[test]
+fn subjective_answer_clusters() {
+    // encoding.md: 객관식 18, 19, 72, 76과 서술형 "기타 바이트 길이: [44]"
+    let answers = ["44", " 44 ", "72 바이트", "72  바이트", "18", ""];
+    let submissions: Vec<Submission> = answers
+        .iter()
+        .enumerate()
+        .map(|(i, &answer)| submission(&i.to_string(), vec![("encoding", answer, answer.trim() == "44")]))
+        .collect();
+    let reports = analyze(&submissions, QUESTIONS);
+
+    let report = reports.iter().find(|v| v.question == "encoding").unwrap();
+    let clusters: Vec<(&str, usize, bool, usize)> = report
+        .subjective
+        .iter()
+        .map(|v| (v.normalized.as_str(), v.count, v.accepted, v.variants.len()))
+        .collect();
+    assert_eq!(clusters, [("44", 2, true, 1), ("72 바이트", 2, false, 2)]);
+    assert_eq!(report.choices.iter().find(|v| v.label == "18").unwrap().count, 1);
+
+    // 서술형 선택지가 없는 문제는 묶을 답이 없음
+    let report = reports.iter().find(|v| v.question == "cache").unwrap();
+    assert!(report.subjective.is_empty());
+}
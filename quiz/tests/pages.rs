@@ -0,0 +1,112 @@
+use axum::body::to_bytes;
+use axum::http::{HeaderMap, header};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use study_test::classroom::participant_page;
+use study_test::config::Config;
+use study_test::question_loader::load_questions;
+use study_test::submission_store::FileSubmissionStore;
+use study_test::{
+    QUESTIONS, QuestionSet, ServiceState, get_session, render_finish_page, render_question, start_session,
+};
+use tokio::sync::{RwLock, broadcast};
+
+#[test]
+fn question_page_escapes_text_and_substitutes_once() {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::write(
+        directory.path().join("generic.md"),
+        "# Option<T> 와 <script>\n\n`&str`을 받는 함수\n\n```rs\nfn f<T>(v: &str) {}\n```\n\n- [x] QUESTION_COUNT\n- [ ] <b>굵게</b>\n",
+    )
+    .unwrap();
+    let questions = load_questions(directory.path()).unwrap();
+    let page = render_question(&questions[0], 7, "<퀴즈> & 제목").0;
+
+    assert!(page.contains("<title>&#60;퀴즈&#62; &#38; 제목</title>"));
+    assert!(page.contains(">&#60;퀴즈&#62; &#38; 제목</p>"));
+    assert!(!page.contains("Coding Lab 2025"));
+    assert!(page.contains("Option&#60;T&#62; 와 &#60;script&#62;"));
+    assert!(page.contains("<code>&amp;str</code>을 받는 함수"));
+    assert!(page.contains("&lt;b&gt;굵게&lt;/b&gt;"));
+    // 선택지에 적힌 글자는 다른 자리 표시자로 다시 바뀌지 않음
+    assert!(page.contains(">QUESTION_COUNT</label>"));
+    assert!(page.contains(r#"const questionId = "generic""#));
+}
+
+#[test]
+fn finish_page_shows_submit_only_when_online() {
+    let page = render_finish_page(None, 3, "퀴즈").0;
+    assert!(page.contains("온라인 제출이 불가능합니다"));
+    assert!(!page.contains("function submit()"));
+    assert!(page.contains("3/3"));
+    assert!(page.contains(">퀴즈</p>"));
+}
+
+fn service_state(directory: &Path) -> ServiceState {
+    ServiceState {
+        config: Config::default(),
+        question_set: RwLock::new(Arc::new(QuestionSet::new(QUESTIONS, None, "test"))),
+        sessions: Default::default(),
+        submission_store: Arc::new(FileSubmissionStore::new(directory.to_path_buf())),
+        leaderboard: Default::default(),
+        leaderboard_updates: broadcast::channel(4).0,
+        classroom: Default::default(),
+        admin_sessions: Default::default(),
+        discord_data: None,
+    }
+}
+
+/// 새 세션을 시작하고 /session 응답을 반환함
+async fn new_session(state: &ServiceState) -> serde_json::Value {
+    let response = start_session(state).await;
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(header::COOKIE, cookie.split(';').next().unwrap().parse().unwrap());
+
+    let body = to_bytes(get_session(state, &headers).await.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn ordering_steps_are_shuffled_per_session() {
+    let directory = tempfile::tempdir().unwrap();
+    let state = service_state(directory.path());
+
+    let mut orders = HashSet::new();
+    for _ in 0..10 {
+        let session = new_session(&state).await;
+        let index = session["sequence"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .position(|id| id == "cargo_workflow")
+            .unwrap();
+        orders.insert(session["orders"][index].to_string());
+    }
+    assert!(orders.len() > 1);
+}
+
+#[tokio::test]
+async fn expired_classroom_participant_gets_new_id() {
+    let directory = tempfile::tempdir().unwrap();
+    let state = service_state(directory.path());
+
+    let response = participant_page(&state, &HeaderMap::new()).await;
+    let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(header::COOKIE, cookie.split(';').next().unwrap().parse().unwrap());
+
+    // 발급한 id는 다시 발급하지 않음
+    let response = participant_page(&state, &headers).await;
+    assert!(!response.headers().contains_key(header::SET_COOKIE));
+
+    state.classroom.remove_expired(Instant::now() - Duration::from_secs(60)).await;
+    let response = participant_page(&state, &headers).await;
+    assert!(!response.headers().contains_key(header::SET_COOKIE));
+
+    state.classroom.remove_expired(Instant::now() + Duration::from_secs(1)).await;
+    let response = participant_page(&state, &headers).await;
+    assert!(response.headers().contains_key(header::SET_COOKIE));
+}
@@ -0,0 +1,15 @@
+use std::path::Path;
+use study_test::QUESTIONS;
+use study_test::question_loader::load_questions;
+
+/// 빌드할 때 포함한 문제와 개발용 로더가 읽은 문제가 같아야 함
+#[test]
+fn runtime_loader_matches_included_questions() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("questions");
+    let loaded = load_questions(&path).unwrap();
+
+    assert_eq!(loaded.len(), QUESTIONS.len());
+    for (included, loaded) in QUESTIONS.iter().zip(loaded) {
+        assert_eq!(serde_json::to_value(included).unwrap(), serde_json::to_value(loaded).unwrap());
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::{Local, TimeZone};
+use study_test::leaderboard::{Leaderboard, LeaderboardUpdate, RankBy};
+use study_test::submission_store::{FileSubmissionStore, SqliteSubmissionStore, Submission, SubmissionStore};
+use std::sync::Arc;
+use study_test::config::Config;
+use study_test::{GradedAnswer, QUESTIONS, QuestionSet, QuizResult, ServiceState, save_answer};
+use tokio::sync::{RwLock, broadcast};
+
+fn submission(member_id: &str, username: &str, minute: u32, correct: &[bool]) -> Submission {
+    let answers: Vec<GradedAnswer> = correct
+        .iter()
+        .enumerate()
+        .map(|(i, &correct)| GradedAnswer {
+            question: format!("q{}", i),
+            answer: vec![if correct { "0" } else { "1" }.to_string()],
+            correct,
+            credit: if correct { 1.0 } else { 0.0 },
+        })
+        .collect();
+    let score = answers.iter().map(|v| v.credit).sum();
+    Submission {
+        id: String::new(),
+        member_id: member_id.to_string(),
+        username: username.to_string(),
+        submitted_at: Local.with_ymd_and_hms(2025, 3, 1, 12, minute, 0).unwrap(),
+        result: QuizResult { answers, score, legacy: false },
+        invalidated: false,
+    }
+}
+
+/// 두 저장소가 같은 결과를 돌려주는지 확인함
+fn check_store(store: &dyn SubmissionStore) {
+    assert!(store.latest_per_member().unwrap().is_empty());
+
+    store.save(&submission("1", "alice", 0, &[false, false])).unwrap();
+    store.save(&submission("2", "bob", 1, &[true, false])).unwrap();
+    store.save(&submission("1", "alice2", 2, &[true, true])).unwrap();
+
+    let all = store.submissions().unwrap();
+    let all: Vec<(&str, f32)> = all.iter().map(|v| (v.member_id.as_str(), v.result.score)).collect();
+    assert_eq!(all, [("1", 0.0), ("2", 1.0), ("1", 2.0)]);
+
+    let mut latest = store.latest_per_member().unwrap();
+    latest.sort_by(|a, b| a.member_id.cmp(&b.member_id));
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[0].member_id, "1");
+    assert_eq!(latest[0].username, "alice2");
+    assert_eq!(latest[0].result.score, 2.0);
+    assert_eq!(latest[0].submitted_at, Local.with_ymd_and_hms(2025, 3, 1, 12, 2, 0).unwrap());
+    assert_eq!(latest[1].member_id, "2");
+    assert_eq!(latest[1].result.answers.len(), 2);
+    assert_eq!(latest[1].result.answers[1].question, "q1");
+
+    let question = store.submissions_for_question("q0").unwrap();
+    let summary: Vec<(&str, bool)> = question
+        .iter()
+        .map(|v| (v.member_id.as_str(), v.answer.correct))
+        .collect();
+    assert_eq!(summary, [("1", false), ("2", true), ("1", true)]);
+    assert_eq!(question[1].answer.answer, ["0"]);
+
+    assert!(store.submissions_for_question("missing").unwrap().is_empty());
+
+    // 무효 처리한 제출은 목록에는 남지만 최신 기록과 문제별 기록에서는 빠짐
+    let alice_latest = all_ids(store)[2].clone();
+    assert!(store.set_invalidated(&alice_latest, true).unwrap());
+    assert!(store.get(&alice_latest).unwrap().unwrap().invalidated);
+    let latest = store.latest_per_member().unwrap();
+    let alice = latest.iter().find(|v| v.member_id == "1").unwrap();
+    assert_eq!(alice.result.score, 0.0);
+    assert_eq!(store.submissions_for_question("q0").unwrap().len(), 2);
+    assert_eq!(store.submissions().unwrap().len(), 3);
+
+    assert!(store.set_invalidated(&alice_latest, false).unwrap());
+    assert!(!store.get(&alice_latest).unwrap().unwrap().invalidated);
+
+    let bob = all_ids(store)[1].clone();
+    assert!(store.delete(&bob).unwrap());
+    assert!(!store.delete(&bob).unwrap());
+    assert!(store.get(&bob).unwrap().is_none());
+    assert!(!store.set_invalidated(&bob, true).unwrap());
+    assert_eq!(store.latest_per_member().unwrap().len(), 1);
+
+    for id in ["", "missing", "../1_2025-03-01T12-00-00", "1_../../etc"] {
+        assert!(store.get(id).unwrap().is_none());
+        assert!(!store.delete(id).unwrap());
+    }
+
+    // 닉네임은 경로로 해석되지 않고 그대로 저장됨
+    let id = store.save(&submission("3", "../x", 3, &[true, true])).unwrap();
+    assert_eq!(store.get(&id).unwrap().unwrap().username, "../x");
+}
+
+fn all_ids(store: &dyn SubmissionStore) -> Vec<String> {
+    store.submissions().unwrap().into_iter().map(|v| v.id).collect()
+}
+
+#[test]
+fn file_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = FileSubmissionStore::new(dir.path().join("submissions"));
+    check_store(&store);
+    assert!(store.save(&submission("../3", "carol", 4, &[true, true])).is_err());
+    // 저장 폴더 밖에는 아무 파일도 만들지 않음
+    let outside: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|v| v.unwrap().file_name()).collect();
+    assert_eq!(outside, ["submissions"]);
+}
+
+#[test]
+fn file_store_reads_baseline_submissions() {
+    // 서버에서 채점하기 전의 버전이 남긴 폴더 구조와 파일 형식
+    let dir = tempfile::tempdir().unwrap();
+    let member = dir.path().join("123");
+    std::fs::create_dir_all(&member).unwrap();
+    std::fs::write(member.join("alice"), "").unwrap();
+    std::fs::write(
+        member.join("2025-03-01T12-00-00.json"),
+        r#"{"answers":[["string_vs_str.md","x"],["borrow.md","y"]],"score":50.0}"#,
+    )
+    .unwrap();
+
+    let store = FileSubmissionStore::new(dir.path().to_path_buf());
+    let submissions = store.submissions().unwrap();
+    assert_eq!(submissions.len(), 1);
+    assert_eq!(submissions[0].username, "alice");
+    assert!(submissions[0].result.legacy);
+    assert_eq!(submissions[0].result.score, 1.0);
+    assert_eq!(submissions[0].result.answers[0].question, "string_vs_str");
+    assert_eq!(submissions[0].result.answers[0].answer, ["x"]);
+    // 문제별 정답 여부를 알 수 없으므로 문제별 기록에는 포함하지 않음
+    assert!(store.submissions_for_question("string_vs_str").unwrap().is_empty());
+
+    let leaderboard = Leaderboard::from_submissions(&submissions);
+    assert_eq!(leaderboard.ranking(RankBy::Best)[0].best_score, 1.0);
+
+    // 새 제출은 같은 폴더에 지금 형식으로 저장됨
+    store.save(&submission("123", "alice", 5, &[true, true])).unwrap();
+    let latest = store.latest_per_member().unwrap();
+    assert_eq!((latest[0].username.as_str(), latest[0].result.legacy), ("alice", false));
+}
+
+#[test]
+fn sqlite_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("submissions.db");
+    check_store(&SqliteSubmissionStore::open(&path).unwrap());
+
+    // 다시 열어도 기록이 남아 있어야 함
+    let store = SqliteSubmissionStore::open(&path).unwrap();
+    assert_eq!(store.submissions().unwrap().len(), 3);
+    assert_eq!(store.latest_per_member().unwrap().len(), 2);
+}
+
+#[test]
+fn leaderboard_ranking() {
+    let leaderboard = Leaderboard::from_submissions(&[
+        submission("1", "alice", 0, &[true, true]),
+        submission("2", "bob", 1, &[true, false]),
+        submission("1", "alice2", 2, &[false, false]),
+        submission("3", "carol", 3, &[true, false]),
+    ]);
+    assert_eq!(leaderboard.len(), 3);
+
+    let best = leaderboard.ranking(RankBy::Best);
+    let best: Vec<(&str, f32)> = best.iter().map(|v| (v.username.as_str(), v.best_score)).collect();
+    assert_eq!(best, [("alice2", 2.0), ("bob", 1.0), ("carol", 1.0)]);
+
+    let latest = leaderboard.ranking(RankBy::Latest);
+    let latest: Vec<(&str, f32, usize)> = latest
+        .iter()
+        .map(|v| (v.username.as_str(), v.latest_score, v.submissions))
+        .collect();
+    assert_eq!(latest, [("bob", 1.0, 1), ("carol", 1.0, 1), ("alice2", 0.0, 2)]);
+
+    let mut invalidated = submission("2", "bob", 4, &[true, true]);
+    invalidated.invalidated = true;
+    let leaderboard = Leaderboard::from_submissions(&[submission("1", "alice", 0, &[true, false]), invalidated]);
+    assert_eq!(leaderboard.len(), 1);
+}
+
+fn service_state(store: Arc<dyn SubmissionStore>) -> ServiceState {
+    ServiceState {
+        config: Config::default(),
+        question_set: RwLock::new(Arc::new(QuestionSet::new(QUESTIONS, None, "test"))),
+        sessions: Default::default(),
+        submission_store: store,
+        leaderboard: Default::default(),
+        leaderboard_updates: broadcast::channel(4).0,
+        classroom: Default::default(),
+        admin_sessions: Default::default(),
+        discord_data: None,
+    }
+}
+
+#[tokio::test]
+async fn save_answer_updates_leaderboard() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(FileSubmissionStore::new(dir.path().to_path_buf()));
+    let state = service_state(store.clone());
+    let mut updates = state.leaderboard_updates.subscribe();
+
+    let result = submission("1", "alice", 0, &[true, false]).result;
+    save_answer(&state, "alice".to_string(), "1".to_string(), result).await.unwrap();
+
+    let LeaderboardUpdate::Entry(update) = updates.recv().await.unwrap() else {
+        panic!("expected a leaderboard entry");
+    };
+    assert_eq!((update.username.as_str(), update.latest_score), ("alice", 1.0));
+    assert_eq!(state.leaderboard.read().await.len(), 1);
+    assert_eq!(store.submissions().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn failed_save_does_not_update_leaderboard() {
+    // 파일을 저장 폴더로 지정해 저장이 항상 실패하게 함
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let state = service_state(Arc::new(FileSubmissionStore::new(file.path().to_path_buf())));
+    let mut updates = state.leaderboard_updates.subscribe();
+
+    let result = submission("1", "alice", 0, &[true, false]).result;
+    assert!(save_answer(&state, "alice".to_string(), "1".to_string(), result).await.is_err());
+
+    assert_eq!(state.leaderboard.read().await.len(), 0);
+    assert!(updates.try_recv().is_err());
+}